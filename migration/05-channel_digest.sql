@@ -0,0 +1,3 @@
+USE reminders;
+
+ALTER TABLE channels ADD COLUMN `digest` BOOL NOT NULL DEFAULT 0;
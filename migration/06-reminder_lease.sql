@@ -0,0 +1,6 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `locked_by` VARCHAR(64);
+ALTER TABLE reminders ADD COLUMN `locked_until` DATETIME;
+
+CREATE INDEX `reminders_locked_by` ON reminders (`locked_by`);
@@ -0,0 +1,5 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `retry_count` INT UNSIGNED NOT NULL DEFAULT 0;
+ALTER TABLE reminders ADD COLUMN `retry_at` DATETIME;
+ALTER TABLE reminders ADD COLUMN `failed` BOOL NOT NULL DEFAULT 0;
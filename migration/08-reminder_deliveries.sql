@@ -0,0 +1,20 @@
+USE reminders;
+
+CREATE TABLE reminder_deliveries (
+    `id` INT UNSIGNED NOT NULL AUTO_INCREMENT,
+
+    `reminder_id` INT UNSIGNED NOT NULL,
+    `reminder_uid` VARCHAR(64) NOT NULL,
+    `channel` BIGINT UNSIGNED NOT NULL,
+
+    `message_id` BIGINT UNSIGNED,
+    `method` ENUM('webhook', 'channel'),
+    `sent_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    `error` VARCHAR(1024),
+    `content` VARCHAR(100) NOT NULL DEFAULT '',
+
+    PRIMARY KEY (id),
+
+    INDEX (`reminder_uid`),
+    INDEX (`channel`, `sent_at`)
+);
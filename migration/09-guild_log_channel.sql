@@ -0,0 +1,3 @@
+USE reminders;
+
+ALTER TABLE guilds ADD COLUMN `log_channel` BIGINT UNSIGNED;
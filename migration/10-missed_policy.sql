@@ -0,0 +1,4 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `missed_policy` ENUM('once', 'all', 'skip', 'digest');
+ALTER TABLE guilds ADD COLUMN `missed_policy` ENUM('once', 'all', 'skip', 'digest') NOT NULL DEFAULT 'once';
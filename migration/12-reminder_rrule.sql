@@ -0,0 +1,4 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `rrule` VARCHAR(255);
+ALTER TABLE reminders ADD COLUMN `occurrences` INT UNSIGNED NOT NULL DEFAULT 0;
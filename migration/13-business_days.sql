@@ -0,0 +1,16 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `interval_weekdays` BOOL NOT NULL DEFAULT 0;
+
+CREATE TABLE guild_holidays (
+    `id` INT UNSIGNED NOT NULL AUTO_INCREMENT,
+
+    `guild_id` INT UNSIGNED NOT NULL,
+    `date` DATE NOT NULL,
+    `name` VARCHAR(100) NOT NULL DEFAULT '',
+
+    PRIMARY KEY (id),
+
+    FOREIGN KEY (`guild_id`) REFERENCES guilds(`id`) ON DELETE CASCADE,
+    UNIQUE KEY (`guild_id`, `date`)
+);
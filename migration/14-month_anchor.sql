@@ -0,0 +1,4 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `month_anchor` ENUM('clamp', 'nth_weekday', 'last_weekday', 'last_day');
+ALTER TABLE reminders ADD COLUMN `month_anchor_day` TINYINT UNSIGNED;
@@ -0,0 +1,4 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `exclude_dates` JSON;
+ALTER TABLE reminders ADD COLUMN `skip_next` BOOL NOT NULL DEFAULT 0;
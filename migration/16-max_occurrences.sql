@@ -0,0 +1,3 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `max_occurrences` INT UNSIGNED;
@@ -0,0 +1,3 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `interval_wallclock` BOOL NOT NULL DEFAULT 0;
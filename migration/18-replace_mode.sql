@@ -0,0 +1,4 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `replace_mode` ENUM('edit', 'repost');
+ALTER TABLE reminders ADD COLUMN `last_message_id` BIGINT UNSIGNED;
@@ -0,0 +1,17 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `delete_after` INT UNSIGNED;
+
+CREATE TABLE pending_deletions (
+    `id` INT UNSIGNED NOT NULL AUTO_INCREMENT,
+
+    `reminder_id` INT UNSIGNED,
+    `channel` BIGINT UNSIGNED NOT NULL,
+    `message_id` BIGINT UNSIGNED NOT NULL,
+    `method` ENUM('webhook', 'channel') NOT NULL,
+    `delete_at` DATETIME NOT NULL,
+
+    PRIMARY KEY (id),
+
+    INDEX (`delete_at`)
+);
@@ -0,0 +1,4 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `thread_id` BIGINT UNSIGNED;
+ALTER TABLE reminders ADD COLUMN `thread_title` VARCHAR(100);
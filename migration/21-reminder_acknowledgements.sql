@@ -0,0 +1,19 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `ack_button` BOOL NOT NULL DEFAULT 0;
+
+CREATE TABLE reminder_acknowledgements (
+    `id` INT UNSIGNED NOT NULL AUTO_INCREMENT,
+
+    `reminder_id` INT UNSIGNED,
+    `occurrence` INT UNSIGNED NOT NULL,
+    `channel` BIGINT UNSIGNED NOT NULL,
+    `message_id` BIGINT UNSIGNED NOT NULL,
+    `user` BIGINT UNSIGNED NOT NULL,
+    `acknowledged_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (id),
+
+    UNIQUE KEY (`message_id`, `occurrence`, `user`),
+    INDEX (`reminder_id`)
+);
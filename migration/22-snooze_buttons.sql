@@ -0,0 +1,3 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `snooze_buttons` BOOL NOT NULL DEFAULT 0;
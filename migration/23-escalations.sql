@@ -0,0 +1,26 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `escalate_after` INT UNSIGNED;
+ALTER TABLE reminders ADD COLUMN `escalate_mentions` VARCHAR(1000);
+ALTER TABLE reminders ADD COLUMN `escalate_user` BIGINT UNSIGNED;
+ALTER TABLE reminders ADD COLUMN `escalate_limit` INT UNSIGNED NOT NULL DEFAULT 1;
+
+CREATE TABLE pending_escalations (
+    `id` INT UNSIGNED NOT NULL AUTO_INCREMENT,
+
+    `reminder_id` INT UNSIGNED,
+    `guild_id` BIGINT UNSIGNED,
+    `channel` BIGINT UNSIGNED NOT NULL,
+    `message_id` BIGINT UNSIGNED NOT NULL,
+    `occurrence` INT UNSIGNED NOT NULL,
+    `mentions` VARCHAR(1000),
+    `backup_user` BIGINT UNSIGNED,
+    `escalate_after` INT UNSIGNED NOT NULL,
+    `remaining` INT UNSIGNED NOT NULL,
+    `escalate_at` DATETIME NOT NULL,
+
+    PRIMARY KEY (id),
+
+    INDEX (`escalate_at`),
+    INDEX (`message_id`, `occurrence`)
+);
@@ -0,0 +1,5 @@
+USE reminders;
+
+ALTER TABLE reminders ADD COLUMN `content_pool` JSON;
+ALTER TABLE reminders ADD COLUMN `content_pool_mode` ENUM('sequential', 'random') NOT NULL DEFAULT 'sequential';
+ALTER TABLE reminders ADD COLUMN `content_pool_cursor` INT UNSIGNED NOT NULL DEFAULT 0;
@@ -0,0 +1,34 @@
+USE reminders;
+
+-- attachments and embeds sent alongside the reminder's own `attachment` and `embed_*` columns
+CREATE TABLE reminder_attachments (
+    `id` INT UNSIGNED NOT NULL AUTO_INCREMENT,
+
+    `reminder_id` INT UNSIGNED NOT NULL,
+    `name` VARCHAR(260) NOT NULL,
+    `data` MEDIUMBLOB NOT NULL,
+
+    PRIMARY KEY (id),
+
+    FOREIGN KEY (`reminder_id`) REFERENCES reminders (`id`) ON DELETE CASCADE
+);
+
+CREATE TABLE reminder_embeds (
+    `id` INT UNSIGNED NOT NULL AUTO_INCREMENT,
+
+    `reminder_id` INT UNSIGNED NOT NULL,
+    `title` VARCHAR(256) NOT NULL DEFAULT '',
+    `description` VARCHAR(4096) NOT NULL DEFAULT '',
+    `image_url` VARCHAR(512),
+    `thumbnail_url` VARCHAR(512),
+    `footer` VARCHAR(2048) NOT NULL DEFAULT '',
+    `footer_url` VARCHAR(512),
+    `author` VARCHAR(256) NOT NULL DEFAULT '',
+    `author_url` VARCHAR(512),
+    `color` INT UNSIGNED NOT NULL DEFAULT 0x0,
+    `fields` JSON,
+
+    PRIMARY KEY (id),
+
+    FOREIGN KEY (`reminder_id`) REFERENCES reminders (`id`) ON DELETE CASCADE
+);
@@ -0,0 +1,6 @@
+USE reminders;
+
+-- `occurrences` counts every step of the schedule, as RRULE `COUNT` does. `max_occurrences` counts
+-- sends, which excludes skipped and missed occurrences
+ALTER TABLE reminders ADD COLUMN `sent_count` INT UNSIGNED NOT NULL DEFAULT 0;
+UPDATE reminders SET `sent_count` = `occurrences`;
@@ -0,0 +1,5 @@
+USE reminders;
+
+-- deletions are claimed by moving `delete_at` back, and stay queued until they succeed, so that
+-- transient errors are retried
+ALTER TABLE pending_deletions ADD COLUMN `attempts` INT UNSIGNED NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+USE reminders;
+
+-- escalations are claimed by moving `escalate_at` back, and stay queued until they are sent, so
+-- that transient errors are retried
+ALTER TABLE pending_escalations ADD COLUMN `attempts` INT UNSIGNED NOT NULL DEFAULT 0;
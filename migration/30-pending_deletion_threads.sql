@@ -0,0 +1,4 @@
+USE reminders;
+
+-- `channel` is the channel whose webhook posted the message, and `thread_id` the thread it's in
+ALTER TABLE pending_deletions ADD COLUMN `thread_id` BIGINT UNSIGNED;
@@ -0,0 +1,8 @@
+USE reminders;
+
+-- when a reminder is next due: its retry if one is scheduled, otherwise its time. Stored so that
+-- postman's sweeps and wakeups can use an index
+ALTER TABLE reminders
+    ADD COLUMN `due_at` DATETIME AS (IFNULL(`retry_at`, `utc_time`)) STORED NOT NULL;
+
+CREATE INDEX `reminders_due` ON reminders (`failed`, `due_at`, `locked_until`);
@@ -0,0 +1,7 @@
+USE reminders;
+
+-- `channel` is the reminder's channel, and `thread_id` the thread the message was posted in
+ALTER TABLE reminder_deliveries
+    ADD COLUMN `thread_id` BIGINT UNSIGNED,
+    ADD INDEX (`thread_id`, `sent_at`),
+    ADD INDEX (`sent_at`);
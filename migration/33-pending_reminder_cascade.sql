@@ -0,0 +1,13 @@
+USE reminders;
+
+-- deleting a reminder cancels its pending escalations and deletions. Reminders that finish
+-- detach theirs first, so those still happen
+UPDATE pending_escalations SET `reminder_id` = NULL
+    WHERE `reminder_id` NOT IN (SELECT `id` FROM reminders);
+UPDATE pending_deletions SET `reminder_id` = NULL
+    WHERE `reminder_id` NOT IN (SELECT `id` FROM reminders);
+
+ALTER TABLE pending_escalations
+    ADD FOREIGN KEY (`reminder_id`) REFERENCES reminders (`id`) ON DELETE CASCADE;
+ALTER TABLE pending_deletions
+    ADD FOREIGN KEY (`reminder_id`) REFERENCES reminders (`id`) ON DELETE CASCADE;
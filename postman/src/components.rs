@@ -0,0 +1,49 @@
+use rmp_serde::Serializer;
+use serde::{Deserialize, Serialize};
+
+/// The components postman attaches to reminders. Variants are serialized the same way as the
+/// bot's `ComponentDataModel`, which handles the interactions, so their names must match. The
+/// bot uses the variants' data types from here.
+#[derive(Serialize)]
+#[serde(tag = "type")]
+pub enum ComponentDataModel {
+    AcknowledgeReminder(AcknowledgeReminder),
+    SnoozeReminder(SnoozeReminder),
+}
+
+impl ComponentDataModel {
+    pub fn to_custom_id(&self) -> String {
+        let mut buf = Vec::new();
+        self.serialize(&mut Serializer::new(&mut buf)).unwrap();
+        base64::encode(buf)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AcknowledgeReminder {
+    pub reminder_id: u32,
+    pub occurrence: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SnoozeReminder {
+    pub minutes: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the bot's `component_models` tests decode the same custom IDs
+    #[test]
+    fn custom_ids_match_bot() {
+        let acknowledge = ComponentDataModel::AcknowledgeReminder(AcknowledgeReminder {
+            reminder_id: 12,
+            occurrence: 3,
+        });
+        assert_eq!(acknowledge.to_custom_id(), "k7NBY2tub3dsZWRnZVJlbWluZGVyDAM=");
+
+        let snooze = ComponentDataModel::SnoozeReminder(SnoozeReminder { minutes: 10 });
+        assert_eq!(snooze.to_custom_id(), "kq5Tbm9vemVSZW1pbmRlcgo=");
+    }
+}
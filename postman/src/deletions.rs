@@ -0,0 +1,156 @@
+use log::{info, warn};
+use serenity::{
+    http::{Http, StatusCode},
+    Error,
+};
+use sqlx::Executor;
+
+use crate::{
+    sender::{is_transient, retry_delay, token_with_query, MAX_ATTEMPTS},
+    Database,
+};
+
+struct PendingDeletion {
+    id: u32,
+    reminder_id: Option<u32>,
+    channel: u64,
+    thread_id: Option<u64>,
+    message_id: u64,
+    method: String,
+    webhook_id: Option<u64>,
+    webhook_token: Option<String>,
+    attempts: u32,
+}
+
+/// Delete reminder messages whose `delete_after` time has passed. Each deletion is claimed by
+/// moving its `delete_at` back, so only one postman attempts it, and its row is only removed once
+/// the message is gone. If the attempt fails for a transient reason, the moved `delete_at` is when
+/// it's tried again.
+pub async fn delete_expired(pool: impl Executor<'_, Database = Database> + Copy, http: &Http) {
+    let pending = match sqlx::query_as_unchecked!(
+        PendingDeletion,
+        "
+SELECT
+    pending_deletions.`id` AS id,
+    pending_deletions.`reminder_id` AS reminder_id,
+    pending_deletions.`channel` AS channel,
+    pending_deletions.`thread_id` AS thread_id,
+    pending_deletions.`message_id` AS message_id,
+    pending_deletions.`method` AS method,
+    channels.`webhook_id` AS webhook_id,
+    channels.`webhook_token` AS webhook_token,
+    pending_deletions.`attempts` AS attempts
+FROM
+    pending_deletions
+LEFT JOIN
+    channels
+ON
+    channels.`channel` = pending_deletions.`channel`
+WHERE
+    pending_deletions.`delete_at` <= NOW()
+ORDER BY
+    pending_deletions.`delete_at`
+        "
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(pending) => pending,
+
+        Err(e) => {
+            warn!("Could not fetch pending deletions: {:?}", e);
+
+            return;
+        }
+    };
+
+    for deletion in pending {
+        match sqlx::query!(
+            "
+UPDATE pending_deletions SET `attempts` = `attempts` + 1, `delete_at` = DATE_ADD(NOW(), INTERVAL \
+             ? SECOND) WHERE `id` = ? AND `delete_at` <= NOW()
+            ",
+            retry_delay(deletion.attempts),
+            deletion.id
+        )
+        .execute(pool)
+        .await
+        {
+            Ok(result) if result.rows_affected() > 0 => {}
+
+            // already claimed by another postman
+            Ok(_) => continue,
+
+            Err(e) => {
+                warn!("Could not claim pending deletion {}: {:?}", deletion.id, e);
+
+                continue;
+            }
+        }
+
+        let channel = deletion.thread_id.unwrap_or(deletion.channel);
+
+        let result = match (deletion.method.as_str(), deletion.webhook_id, &deletion.webhook_token)
+        {
+            ("webhook", Some(webhook_id), Some(webhook_token)) => {
+                let token = match deletion.thread_id {
+                    Some(thread_id) => token_with_query(
+                        webhook_token,
+                        &format!("/messages/{}?thread_id={}", deletion.message_id, thread_id),
+                    ),
+                    None => webhook_token.clone(),
+                };
+
+                match http.delete_webhook_message(webhook_id, &token, deletion.message_id).await {
+                    Ok(()) => Ok(()),
+
+                    // the webhook may have been replaced since, so try as the bot instead
+                    Err(_) => http.delete_message(channel, deletion.message_id).await,
+                }
+            }
+
+            _ => http.delete_message(channel, deletion.message_id).await,
+        };
+
+        match result {
+            // already deleted by someone else
+            Err(Error::Http(e)) if e.status_code() == Some(StatusCode::NOT_FOUND) => {}
+
+            Err(e) if is_transient(&e) && deletion.attempts + 1 < *MAX_ATTEMPTS => {
+                info!(
+                    "Could not delete message {} of Reminder {:?}, will retry: {:?}",
+                    deletion.message_id, deletion.reminder_id, e
+                );
+
+                continue;
+            }
+
+            Err(e) => {
+                warn!(
+                    "Could not delete message {} of Reminder {:?}: {:?}",
+                    deletion.message_id, deletion.reminder_id, e
+                );
+            }
+
+            Ok(()) => {}
+        }
+
+        if let Err(e) = sqlx::query!("DELETE FROM pending_deletions WHERE `id` = ?", deletion.id)
+            .execute(pool)
+            .await
+        {
+            warn!("Could not remove pending deletion {}: {:?}", deletion.id, e);
+        }
+
+        // nothing is left to mark done
+        if let Err(e) = sqlx::query!(
+            "DELETE FROM pending_escalations WHERE `message_id` = ?",
+            deletion.message_id
+        )
+        .execute(pool)
+        .await
+        {
+            warn!("Could not remove escalations of message {}: {:?}", deletion.message_id, e);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use log::warn;
+use serenity::{
+    http::Http,
+    model::id::{ChannelId, MessageId, UserId},
+    Result,
+};
+use sqlx::Executor;
+
+use crate::{
+    sender::{is_transient, retry_delay, MAX_ATTEMPTS},
+    Database,
+};
+
+struct PendingEscalation {
+    id: u32,
+    reminder_id: Option<u32>,
+    guild_id: Option<u64>,
+    channel: u64,
+    message_id: u64,
+    mentions: Option<String>,
+    backup_user: Option<u64>,
+    escalate_after: u32,
+    remaining: u32,
+    attempts: u32,
+}
+
+impl PendingEscalation {
+    fn link(&self) -> String {
+        format!(
+            "https://discord.com/channels/{}/{}/{}",
+            self.guild_id.map_or("@me".to_string(), |id| id.to_string()),
+            self.channel,
+            self.message_id
+        )
+    }
+
+    fn can_retry(&self) -> bool {
+        self.attempts + 1 < *MAX_ATTEMPTS
+    }
+
+    async fn reply(&self, http: &Http, mentions: &str) -> Result<()> {
+        ChannelId(self.channel)
+            .send_message(http, |m| {
+                m.content(format!("{} This reminder hasn't been marked done yet.", mentions))
+                    .reference_message((ChannelId(self.channel), MessageId(self.message_id)))
+            })
+            .await
+            .map(|_| ())
+    }
+
+    async fn notify_backup(&self, http: &Http, backup_user: u64) -> Result<()> {
+        UserId(backup_user)
+            .create_dm_channel(http)
+            .await?
+            .say(
+                http,
+                format!(
+                    "A reminder you are the backup for hasn't been marked done yet: {}",
+                    self.link()
+                ),
+            )
+            .await
+            .map(|_| ())
+    }
+
+    /// Queue the next repeat of the escalation, or remove it if it has none left
+    async fn done(&self, pool: impl Executor<'_, Database = Database> + Copy) {
+        let result = if self.remaining > 1 {
+            sqlx::query!(
+                "
+UPDATE pending_escalations SET `remaining` = `remaining` - 1, `attempts` = 0, `escalate_at` = \
+                 DATE_ADD(NOW(), INTERVAL ? SECOND) WHERE `id` = ?
+                ",
+                self.escalate_after,
+                self.id
+            )
+            .execute(pool)
+            .await
+        } else {
+            sqlx::query!("DELETE FROM pending_escalations WHERE `id` = ?", self.id)
+                .execute(pool)
+                .await
+        };
+
+        if let Err(e) = result {
+            warn!("Could not update pending escalation {}: {:?}", self.id, e);
+        }
+    }
+}
+
+/// Escalate reminders that nobody has marked done within `escalate_after`. The reminder's message
+/// is replied to with the escalation mentions and the backup user is sent a direct message. Each
+/// escalation is claimed by moving its `escalate_at` back, so it's tried again then if sending
+/// fails for a transient reason, and is queued again while it has repeats remaining.
+pub async fn escalate_due(pool: impl Executor<'_, Database = Database> + Copy, http: &Http) {
+    let pending = match sqlx::query_as_unchecked!(
+        PendingEscalation,
+        "
+SELECT
+    pending_escalations.`id` AS id,
+    pending_escalations.`reminder_id` AS reminder_id,
+    pending_escalations.`guild_id` AS guild_id,
+    pending_escalations.`channel` AS channel,
+    pending_escalations.`message_id` AS message_id,
+    pending_escalations.`mentions` AS mentions,
+    pending_escalations.`backup_user` AS backup_user,
+    pending_escalations.`escalate_after` AS escalate_after,
+    pending_escalations.`remaining` AS remaining,
+    pending_escalations.`attempts` AS attempts
+FROM
+    pending_escalations
+WHERE
+    pending_escalations.`escalate_at` <= NOW()
+    AND NOT EXISTS (
+        SELECT 1 FROM reminder_acknowledgements
+        WHERE
+            reminder_acknowledgements.`message_id` = pending_escalations.`message_id`
+            AND reminder_acknowledgements.`occurrence` = pending_escalations.`occurrence`
+    )
+ORDER BY
+    pending_escalations.`escalate_at`
+        "
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(pending) => pending,
+
+        Err(e) => {
+            warn!("Could not fetch pending escalations: {:?}", e);
+
+            return;
+        }
+    };
+
+    for escalation in pending {
+        match sqlx::query!(
+            "
+UPDATE pending_escalations SET `attempts` = `attempts` + 1, `escalate_at` = DATE_ADD(NOW(), \
+             INTERVAL ? SECOND) WHERE `id` = ? AND `escalate_at` <= NOW()
+            ",
+            retry_delay(escalation.attempts),
+            escalation.id
+        )
+        .execute(pool)
+        .await
+        {
+            Ok(result) if result.rows_affected() > 0 => {}
+
+            // already claimed by another postman, or acknowledged
+            Ok(_) => continue,
+
+            Err(e) => {
+                warn!("Could not claim pending escalation {}: {:?}", escalation.id, e);
+
+                continue;
+            }
+        }
+
+        // the backup user is only messaged once the reply has gone through, so that a retry
+        // doesn't send either twice
+        if let Some(mentions) = &escalation.mentions {
+            match escalation.reply(http, mentions).await {
+                Ok(()) => {}
+
+                Err(e) if is_transient(&e) && escalation.can_retry() => {
+                    warn!(
+                        "Could not escalate Reminder {:?}, will retry: {:?}",
+                        escalation.reminder_id, e
+                    );
+
+                    continue;
+                }
+
+                Err(e) => {
+                    warn!("Could not escalate Reminder {:?}: {:?}", escalation.reminder_id, e);
+                }
+            }
+        }
+
+        if let Some(backup_user) = escalation.backup_user {
+            match escalation.notify_backup(http, backup_user).await {
+                Ok(()) => {}
+
+                // retrying would repeat the reply, so only retry if there wasn't one
+                Err(e)
+                    if escalation.mentions.is_none()
+                        && is_transient(&e)
+                        && escalation.can_retry() =>
+                {
+                    warn!(
+                        "Could not send escalation of Reminder {:?} to backup, will retry: {:?}",
+                        escalation.reminder_id, e
+                    );
+
+                    continue;
+                }
+
+                Err(e) => {
+                    warn!(
+                        "Could not send escalation of Reminder {:?} to backup: {:?}",
+                        escalation.reminder_id, e
+                    );
+                }
+            }
+        }
+
+        escalation.done(pool).await;
+    }
+}
@@ -1,12 +1,29 @@
+pub mod components;
+mod deletions;
+mod escalations;
+pub mod schedule;
 mod sender;
+pub mod template;
 
-use std::env;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    env, process,
+    sync::{Arc, Mutex},
+};
 
 use log::{info, warn};
 use serenity::client::Context;
-use sqlx::{Executor, MySql};
+use sqlx::{
+    types::chrono::{NaiveDateTime, Utc},
+    MySql, Pool,
+};
 use tokio::{
-    sync::broadcast::Receiver,
+    sync::{
+        broadcast::Receiver,
+        mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+        Semaphore,
+    },
     time::{sleep_until, Duration, Instant},
 };
 
@@ -15,10 +32,11 @@ type Database = MySql;
 pub async fn initialize(
     mut kill: Receiver<()>,
     ctx: Context,
-    pool: impl Executor<'_, Database = Database> + Copy,
+    pool: Pool<Database>,
+    wakeup: UnboundedReceiver<NaiveDateTime>,
 ) -> Result<(), &'static str> {
     tokio::select! {
-        output = _initialize(ctx, pool) => Ok(output),
+        output = _initialize(ctx, pool, wakeup) => Ok(output),
         _ = kill.recv() => {
             warn!("Received terminate signal. Goodbye");
             Err("Received terminate signal. Goodbye")
@@ -26,25 +44,187 @@ pub async fn initialize(
     }
 }
 
-async fn _initialize(ctx: Context, pool: impl Executor<'_, Database = Database> + Copy) {
+async fn _initialize(
+    ctx: Context,
+    pool: Pool<Database>,
+    mut wakeup: UnboundedReceiver<NaiveDateTime>,
+) {
     let remind_interval = env::var("REMIND_INTERVAL")
         .map(|inner| inner.parse::<u64>().ok())
         .ok()
         .flatten()
-        .unwrap_or(10);
+        .unwrap_or(60);
+
+    let remind_workers = env::var("REMIND_WORKERS")
+        .map(|inner| inner.parse::<usize>().ok())
+        .ok()
+        .flatten()
+        .unwrap_or(8);
+
+    let delivery_retention = env::var("DELIVERY_RETENTION")
+        .map(|inner| inner.parse::<u32>().ok())
+        .ok()
+        .flatten()
+        .unwrap_or(90);
+
+    let postman_id = env::var("POSTMAN_ID")
+        .unwrap_or_else(|_| format!("postman-{}-{}", process::id(), Utc::now().timestamp_millis()));
+
+    info!("Starting postman as {}", postman_id);
+
+    let workers = Arc::new(Semaphore::new(remind_workers));
+    let in_flight = Arc::new(Mutex::new(HashMap::new()));
+    let (requeue, mut requeued) = unbounded_channel();
+
+    // upcoming reminder times, earliest first. Entries may be stale (e.g. the reminder was
+    // deleted or moved later); that only costs an extra query
+    let mut upcoming: BinaryHeap<Reverse<NaiveDateTime>> = BinaryHeap::new();
+    let mut next_sweep = Instant::now();
 
     loop {
-        let sleep_to = Instant::now() + Duration::from_secs(remind_interval);
-        let reminders = sender::Reminder::fetch_reminders(pool).await;
+        let now = Utc::now().naive_utc();
+
+        if next_sweep <= Instant::now() || upcoming.peek().map_or(false, |Reverse(t)| *t <= now) {
+            let fetched_at = Instant::now();
+            let reminders =
+                sender::Reminder::fetch_reminders(&pool, &postman_id, *sender::REMIND_LEASE).await;
+
+            if reminders.len() > 0 {
+                info!("Preparing to send {} reminders.", reminders.len());
+
+                dispatch(reminders, &ctx, &pool, &workers, &in_flight, &requeue);
+            }
+
+            deletions::delete_expired(&pool, &ctx.http).await;
+            escalations::escalate_due(&pool, &ctx.http).await;
+            sender::prune_deliveries(&pool, delivery_retention).await;
+
+            if next_sweep <= fetched_at {
+                next_sweep = fetched_at + Duration::from_secs(remind_interval);
+            }
+
+            upcoming.clear();
+            upcoming.extend(
+                sender::Reminder::fetch_upcoming(&pool, remind_interval)
+                    .await
+                    .into_iter()
+                    .map(Reverse),
+            );
+
+            // anything still due was either just inserted or failed to update, so don't spin on it
+            let earliest = fetched_at + Duration::from_secs(1);
+            if Instant::now() < earliest {
+                sleep_until(earliest).await;
+            }
+
+            continue;
+        }
+
+        let sleep_to = upcoming.peek().map_or(next_sweep, |Reverse(t)| {
+            let until = (*t - Utc::now().naive_utc()).to_std().unwrap_or(Duration::ZERO);
 
-        if reminders.len() > 0 {
-            info!("Preparing to send {} reminders.", reminders.len());
+            next_sweep.min(Instant::now() + until)
+        });
 
-            for reminder in reminders {
-                reminder.send(pool, ctx.clone()).await;
+        tokio::select! {
+            _ = sleep_until(sleep_to) => {}
+            Some(time) = wakeup.recv() => {
+                upcoming.push(Reverse(time));
             }
+            Some(time) = requeued.recv() => {
+                upcoming.push(Reverse(time));
+            }
+        }
+    }
+}
+
+/// Removes a channel from the channels being sent to when its task ends, including by panicking.
+/// If reminders on the channel were skipped meanwhile, a wakeup is queued to fetch them again.
+struct InFlight {
+    channels: Arc<Mutex<HashMap<u64, bool>>>,
+    channel: u64,
+    requeue: UnboundedSender<NaiveDateTime>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if let Ok(mut channels) = self.channels.lock() {
+            if channels.remove(&self.channel) == Some(true) {
+                let _ = self.requeue.send(Utc::now().naive_utc());
+            }
+        }
+    }
+}
+
+/// Send reminders concurrently, one task per channel. Reminders on the same channel are sent in
+/// the order they were fetched, so a channel's whole backlog is drained in one pass while
+/// serenity's ratelimiter paces the requests. The number of channels being sent to at once is
+/// bounded by `workers`.
+///
+/// Tasks aren't waited on, so a slow channel doesn't hold up the next sweep. Channels still being
+/// sent to are skipped; their reminders stay leased to this instance, and are fetched again as
+/// soon as the channel's task ends.
+fn dispatch(
+    reminders: Vec<sender::Reminder>,
+    ctx: &Context,
+    pool: &Pool<Database>,
+    workers: &Arc<Semaphore>,
+    in_flight: &Arc<Mutex<HashMap<u64, bool>>>,
+    requeue: &UnboundedSender<NaiveDateTime>,
+) {
+    let mut queues: HashMap<u64, Vec<sender::Reminder>> = HashMap::new();
+
+    for reminder in reminders {
+        queues.entry(reminder.channel_id).or_default().push(reminder);
+    }
+
+    for (channel, queue) in queues {
+        let started =
+            in_flight.lock().map_or(false, |mut channels| match channels.get_mut(&channel) {
+                Some(skipped) => {
+                    *skipped = true;
+
+                    false
+                }
+                None => {
+                    channels.insert(channel, false);
+
+                    true
+                }
+            });
+
+        if !started {
+            continue;
         }
 
-        sleep_until(sleep_to).await;
+        let guard = InFlight { channels: in_flight.clone(), channel, requeue: requeue.clone() };
+        let ctx = ctx.clone();
+        let pool = pool.clone();
+        let workers = workers.clone();
+
+        tokio::spawn(async move {
+            let _guard = guard;
+            let _permit = workers.acquire_owned().await;
+
+            let (missed, queue) = sender::take_missed(queue);
+
+            if !missed.is_empty() {
+                sender::send_missed(missed, &pool, ctx.clone()).await;
+            }
+
+            if queue.is_empty() {
+                return;
+            }
+
+            if queue[0].channel_digest {
+                for group in sender::group_digest(queue) {
+                    sender::Reminder::send_digest(group, &pool, ctx.clone()).await;
+                }
+            } else {
+                for reminder in queue {
+                    reminder.send(&pool, ctx.clone()).await;
+                }
+            }
+        });
     }
 }
@@ -0,0 +1,774 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Weekday};
+use chrono_tz::Tz;
+use cron::Schedule;
+use log::warn;
+use num_integer::Integer;
+use sqlx::Executor;
+
+use crate::Database;
+
+/// Parse a cron expression. Five-field expressions are read as standard crontab (minute, hour, day
+/// of month, month, day of week, with Sunday as 0 or 7). Six- and seven-field expressions are
+/// passed to the `cron` crate unchanged, so they start with seconds and count Sunday as 1.
+pub fn parse_cron(expression: &str) -> Result<Schedule, String> {
+    let fields = expression.split_whitespace().collect::<Vec<&str>>();
+
+    let normalized = if fields.len() == 5 {
+        format!(
+            "0 {} {} {} {} {}",
+            fields[0],
+            fields[1],
+            fields[2],
+            fields[3],
+            translate_weekdays(fields[4])
+        )
+    } else {
+        fields.join(" ")
+    };
+
+    Schedule::from_str(&normalized).map_err(|e| e.to_string())
+}
+
+/// Convert a crontab day-of-week field (0-7, Sunday first and last) to the `cron` crate's (1-7,
+/// Sunday first). Names such as `MON-FRI` are left as they are.
+fn translate_weekdays(field: &str) -> String {
+    field
+        .split(',')
+        .map(|item| {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, Some(step)),
+                None => (item, None),
+            };
+
+            let translated = match range.split_once('-') {
+                Some((start, end)) => match (start.parse::<u8>(), end.parse::<u8>()) {
+                    (Ok(start), Ok(end)) => {
+                        let (start, end) = (start % 7 + 1, end % 7 + 1);
+
+                        if start <= end {
+                            format!("{}-{}", start, end)
+                        } else {
+                            // ranges ending on Sunday as 7, e.g. `5-7`
+                            format!("{}-7,1-{}", start, end)
+                        }
+                    }
+
+                    _ => range.to_string(),
+                },
+
+                None => match range.parse::<u8>() {
+                    Ok(day) => (day % 7 + 1).to_string(),
+                    Err(_) => range.to_string(),
+                },
+            };
+
+            match step {
+                Some(step) => format!("{}/{}", translated, step),
+                None => translated,
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// The shortest gap in seconds between the next few occurrences of a schedule in `timezone`, used
+/// to hold cron reminders to the same minimum interval as other repeating reminders.
+pub fn shortest_interval(schedule: &Schedule, timezone: Tz) -> Option<i64> {
+    schedule
+        .upcoming(timezone)
+        .take(16)
+        .collect::<Vec<DateTime<Tz>>>()
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).num_seconds())
+        .min()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    /// Periods in the 400 years after which the Gregorian calendar repeats, so a search over this
+    /// many periods finds an occurrence if the rule has one.
+    fn cycle(&self) -> i64 {
+        match self {
+            Frequency::Daily => 146097,
+            Frequency::Weekly => 20871,
+            Frequency::Monthly => 4800,
+            Frequency::Yearly => 400,
+        }
+    }
+}
+
+/// Largest `INTERVAL` accepted for new rules
+const MAX_RRULE_INTERVAL: u32 = 366;
+
+enum Until {
+    Utc(NaiveDateTime),
+    Local(NaiveDateTime),
+}
+
+/// An iCalendar (RFC 5545) recurrence rule. `FREQ` (daily or longer), `INTERVAL`, `BYDAY`,
+/// `BYMONTHDAY`, `BYMONTH`, `WKST`, `COUNT` and `UNTIL` are supported. The rule has no `DTSTART`
+/// of its own: occurrences keep the local time of day of the reminder, and `INTERVAL` is counted
+/// from the period holding the reminder's current time.
+pub struct RecurrenceRule {
+    freq: Frequency,
+    interval: u32,
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+    week_start: Weekday,
+    count: Option<u32>,
+    until: Option<Until>,
+}
+
+pub fn parse_rrule(rule: &str) -> Result<RecurrenceRule, String> {
+    rule.parse()
+}
+
+/// Parse a recurrence rule being set on a reminder, also checking that its `INTERVAL` is in range
+/// and that it matches some date. The latter takes a scan of the calendar, so is left out of
+/// `parse_rrule`.
+pub fn validate_rrule(rule: &str) -> Result<RecurrenceRule, String> {
+    let parsed = parse_rrule(rule)?;
+
+    if parsed.interval > MAX_RRULE_INTERVAL {
+        Err(format!("`INTERVAL` must be between 1 and {}", MAX_RRULE_INTERVAL))
+    } else if !parsed.can_match() {
+        Err("The rule doesn't match any date".to_string())
+    } else {
+        Ok(parsed)
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let rule = rule.trim();
+        let rule = match rule.get(..6) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("RRULE:") => &rule[6..],
+            _ => rule,
+        };
+
+        let mut parsed = RecurrenceRule {
+            freq: Frequency::Daily,
+            interval: 1,
+            by_day: vec![],
+            by_month_day: vec![],
+            by_month: vec![],
+            week_start: Weekday::Mon,
+            count: None,
+            until: None,
+        };
+        let mut freq = None;
+
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Expected `NAME=VALUE`, found `{}`", part))?;
+            let value = value.to_ascii_uppercase();
+
+            match name.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("Unsupported frequency `{}`", value)),
+                    })
+                }
+
+                "INTERVAL" => {
+                    parsed.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(|| format!("Invalid interval `{}`", value))?
+                }
+
+                "BYDAY" => {
+                    parsed.by_day =
+                        value.split(',').map(parse_weekday_num).collect::<Result<_, _>>()?
+                }
+
+                "BYMONTHDAY" => {
+                    parsed.by_month_day = value
+                        .split(',')
+                        .map(|day| {
+                            day.parse::<i32>()
+                                .ok()
+                                .filter(|day| *day != 0 && day.abs() <= 31)
+                                .ok_or_else(|| format!("Invalid day of month `{}`", day))
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+
+                "BYMONTH" => {
+                    parsed.by_month = value
+                        .split(',')
+                        .map(|month| {
+                            month
+                                .parse::<u32>()
+                                .ok()
+                                .filter(|month| (1..=12).contains(month))
+                                .ok_or_else(|| format!("Invalid month `{}`", month))
+                        })
+                        .collect::<Result<_, _>>()?
+                }
+
+                "WKST" => parsed.week_start = parse_weekday(&value)?,
+
+                "COUNT" => {
+                    parsed.count = Some(
+                        value
+                            .parse()
+                            .ok()
+                            .filter(|count| *count > 0)
+                            .ok_or_else(|| format!("Invalid count `{}`", value))?,
+                    )
+                }
+
+                "UNTIL" => parsed.until = Some(parse_until(&value)?),
+
+                _ => return Err(format!("Unsupported rule part `{}`", name)),
+            }
+        }
+
+        parsed.freq = freq.ok_or_else(|| "`FREQ` is required".to_string())?;
+
+        if parsed.count.is_some() && parsed.until.is_some() {
+            Err("`COUNT` and `UNTIL` can't be used together".to_string())
+        } else if parsed.by_day.iter().any(|(nth, _)| nth.is_some())
+            && !matches!(parsed.freq, Frequency::Monthly | Frequency::Yearly)
+        {
+            Err("Numbered `BYDAY` values need a monthly or yearly `FREQ`".to_string())
+        } else {
+            Ok(parsed)
+        }
+    }
+}
+
+impl RecurrenceRule {
+    /// The first occurrence after `time`, or at `time` if `inclusive`. `COUNT` and `UNTIL` aren't
+    /// applied, see `has_ended`.
+    pub fn following<Z: TimeZone>(
+        &self,
+        time: &DateTime<Z>,
+        inclusive: bool,
+    ) -> Option<DateTime<Z>> {
+        let start = time.naive_local();
+        let first = self.period(start.date());
+
+        // the periods stepped through repeat within a calendar cycle
+        (0..self.freq.cycle())
+            .map(|step| first + step * self.interval as i64)
+            .flat_map(|period| self.period_dates(period))
+            .filter(|date| *date >= start.date() && self.matches(start.date(), *date))
+            .filter_map(|date| resolve_local(&time.timezone(), date.and_time(start.time())))
+            .find(|candidate| candidate > time || (inclusive && candidate == time))
+    }
+
+    /// Whether an occurrence at `time`, with `occurrences` occurrences before it, is past the end
+    /// of the rule.
+    pub fn has_ended<Z: TimeZone>(&self, time: &DateTime<Z>, occurrences: u32) -> bool {
+        self.count.map_or(false, |count| occurrences >= count)
+            || match self.until {
+                Some(Until::Utc(until)) => time.naive_utc() > until,
+                Some(Until::Local(until)) => time.naive_local() > until,
+                None => false,
+            }
+    }
+
+    /// Whether any date matches the rule's `BY*` parts, e.g. not `BYMONTH=2;BYMONTHDAY=30`.
+    fn can_match(&self) -> bool {
+        // the calendar repeats every 400 years
+        NaiveDate::from_ymd_opt(2000, 1, 1).map_or(true, |first| {
+            (0..146097)
+                .map_while(|offset| first.checked_add_signed(Duration::days(offset)))
+                .any(|date| self.matches(date, date))
+        })
+    }
+
+    fn period(&self, date: NaiveDate) -> i64 {
+        match self.freq {
+            Frequency::Daily => date.num_days_from_ce() as i64,
+            Frequency::Weekly => {
+                (date.num_days_from_ce() as i64 - 1 - self.week_start.num_days_from_monday() as i64)
+                    .div_euclid(7)
+            }
+            Frequency::Monthly => date.year() as i64 * 12 + date.month0() as i64,
+            Frequency::Yearly => date.year() as i64,
+        }
+    }
+
+    /// The dates in a period numbered as by `period`. Empty past the ends of the calendar.
+    fn period_dates(&self, period: i64) -> Vec<NaiveDate> {
+        let first = match self.freq {
+            Frequency::Daily => {
+                i32::try_from(period).ok().and_then(NaiveDate::from_num_days_from_ce_opt)
+            }
+            Frequency::Weekly => {
+                i32::try_from(period * 7 + 1 + self.week_start.num_days_from_monday() as i64)
+                    .ok()
+                    .and_then(NaiveDate::from_num_days_from_ce_opt)
+            }
+            Frequency::Monthly => i32::try_from(period.div_euclid(12)).ok().and_then(|year| {
+                NaiveDate::from_ymd_opt(year, period.rem_euclid(12) as u32 + 1, 1)
+            }),
+            Frequency::Yearly => {
+                i32::try_from(period).ok().and_then(|year| NaiveDate::from_ymd_opt(year, 1, 1))
+            }
+        };
+
+        first.map_or(vec![], |first| {
+            first.iter_days().take_while(|date| self.period(*date) == period).collect()
+        })
+    }
+
+    fn matches(&self, start: NaiveDate, date: NaiveDate) -> bool {
+        if !self.by_month.is_empty() && !self.by_month.contains(&date.month()) {
+            return false;
+        }
+
+        if !self.by_month_day.is_empty()
+            && !self.by_month_day.iter().any(|day| {
+                if *day > 0 {
+                    date.day() as i32 == *day
+                } else {
+                    days_in_month(date) as i32 - date.day() as i32 + 1 == -day
+                }
+            })
+        {
+            return false;
+        }
+
+        if !self.by_day.is_empty() {
+            return self.by_day.iter().any(|(nth, weekday)| {
+                date.weekday() == *weekday && nth.map_or(true, |nth| self.is_nth_weekday(date, nth))
+            });
+        }
+
+        if !self.by_month_day.is_empty() {
+            return true;
+        }
+
+        // without BYDAY or BYMONTHDAY, the day is taken from the reminder's current time
+        match self.freq {
+            Frequency::Daily => true,
+            Frequency::Weekly => date.weekday() == start.weekday(),
+            Frequency::Monthly => date.day() == start.day(),
+            Frequency::Yearly => {
+                date.day() == start.day()
+                    && (!self.by_month.is_empty() || date.month() == start.month())
+            }
+        }
+    }
+
+    /// Numbered weekdays count within the month, or within the year for yearly rules without
+    /// `BYMONTH`. Negative numbers count from the end.
+    fn is_nth_weekday(&self, date: NaiveDate, nth: i32) -> bool {
+        let (index, length) = if self.freq == Frequency::Yearly && self.by_month.is_empty() {
+            let length =
+                if NaiveDate::from_ymd_opt(date.year(), 2, 29).is_some() { 366 } else { 365 };
+
+            (date.ordinal() as i32, length)
+        } else {
+            (date.day() as i32, days_in_month(date) as i32)
+        };
+
+        if nth > 0 {
+            (index - 1) / 7 + 1 == nth
+        } else {
+            (length - index) / 7 + 1 == -nth
+        }
+    }
+}
+
+/// The time in `timezone` at a local date and time. Ambiguous times take the earlier instant, and
+/// times skipped by a DST change are moved an hour forward.
+fn resolve_local<Z: TimeZone>(timezone: &Z, local: NaiveDateTime) -> Option<DateTime<Z>> {
+    timezone
+        .from_local_datetime(&local)
+        .earliest()
+        .or_else(|| timezone.from_local_datetime(&(local + Duration::hours(1))).earliest())
+}
+
+/// Move a time forward by `seconds` of local wall-clock time, so that e.g. a reminder every 12
+/// hours stays at 08:00 and 20:00 across DST changes. Times skipped by a spring-forward change are
+/// moved an hour forward, and times repeated by a fall-back change take the first of the two.
+pub fn add_wallclock<Z: TimeZone>(time: &DateTime<Z>, seconds: i64) -> Option<DateTime<Z>> {
+    resolve_local(&time.timezone(), time.naive_local() + Duration::seconds(seconds))
+}
+
+/// Move a time forward by a repeat interval in seconds. Whole days, and other intervals if
+/// `wallclock` is set, are added in local time so the reminder keeps its time of day over DST.
+/// Other intervals are exact.
+pub fn add_interval<Z: TimeZone>(
+    time: &DateTime<Z>,
+    seconds: i64,
+    wallclock: bool,
+) -> Option<DateTime<Z>> {
+    // `DateTime::checked_add_days` adds to the UTC date and time, so isn't used here
+    if wallclock || seconds.div_rem(&86400).1 == 0 {
+        add_wallclock(time, seconds)
+    } else {
+        time.clone().checked_add_signed(Duration::seconds(seconds))
+    }
+}
+
+/// Move a time forward by whole months in its own timezone, choosing the day in the new month by
+/// `anchor`:
+///  - `clamp`: `anchor_day` (or the current day), or the last day of months too short for it
+///  - `nth_weekday`: the same weekday in the same week of the month, e.g. the first Monday. The
+///    fifth of a weekday counts as the last
+///  - `last_weekday`: the last of the same weekday, e.g. the last Friday
+///  - `last_day`: the last day of the month
+pub fn add_months_anchored<Z: TimeZone>(
+    time: &DateTime<Z>,
+    months: u32,
+    anchor: &str,
+    anchor_day: Option<u32>,
+) -> Option<DateTime<Z>> {
+    let local = time.naive_local();
+    let date = local.date();
+
+    let month_index = date.year() * 12 + date.month0() as i32 + months as i32;
+    let first = NaiveDate::from_ymd_opt(
+        month_index.div_euclid(12),
+        month_index.rem_euclid(12) as u32 + 1,
+        1,
+    )?;
+    let length = days_in_month(first);
+
+    let weekday_offset = |from: Weekday, to: Weekday| {
+        (to.num_days_from_monday() + 7 - from.num_days_from_monday()) % 7
+    };
+    let last_weekday = length - weekday_offset(date.weekday(), first.with_day(length)?.weekday());
+
+    let day = match anchor {
+        "nth_weekday" => {
+            let nth = (date.day() - 1) / 7;
+
+            if nth < 4 {
+                1 + weekday_offset(first.weekday(), date.weekday()) + 7 * nth
+            } else {
+                last_weekday
+            }
+        }
+        "last_weekday" => last_weekday,
+        "last_day" => length,
+        _ => anchor_day.unwrap_or(date.day()).min(length),
+    };
+
+    resolve_local(&time.timezone(), first.with_day(day)?.and_time(local.time()))
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) =
+        if date.month() == 12 { (date.year() + 1, 1) } else { (date.year(), date.month() + 1) };
+
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|first| first.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+fn parse_weekday(day: &str) -> Result<Weekday, String> {
+    match day {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(format!("Invalid weekday `{}`", day)),
+    }
+}
+
+/// A `BYDAY` value such as `MO`, `1MO` or `-1FR`.
+fn parse_weekday_num(value: &str) -> Result<(Option<i32>, Weekday), String> {
+    let split = value.len().checked_sub(2).filter(|split| value.is_char_boundary(*split));
+    let (nth, day) = split.map_or(("", value), |split| value.split_at(split));
+
+    let nth = match nth {
+        "" => None,
+        nth => Some(
+            nth.parse::<i32>()
+                .ok()
+                .filter(|nth| *nth != 0 && nth.abs() <= 53)
+                .ok_or_else(|| format!("Invalid weekday `{}`", value))?,
+        ),
+    };
+
+    Ok((nth, parse_weekday(day)?))
+}
+
+/// `UNTIL` is either a UTC time (`19970714T173000Z`), a local time, or a date, which includes the
+/// whole day.
+fn parse_until(value: &str) -> Result<Until, String> {
+    let error = |_| format!("Invalid end time `{}`", value);
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map(Until::Utc).map_err(error)
+    } else if value.contains('T') {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map(Until::Local).map_err(error)
+    } else {
+        NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(23, 59, 59))
+            .map(Until::Local)
+            .ok_or_else(|| format!("Invalid end time `{}`", value))
+    }
+}
+
+/// Move a time forward by whole days until it falls on a weekday that isn't one of `holidays`.
+/// Holidays are dates in the time's own timezone.
+pub fn next_business_day<Z: TimeZone>(time: DateTime<Z>, holidays: &[NaiveDate]) -> DateTime<Z> {
+    let mut next = time.clone();
+
+    // bounded so a holiday list covering every weekday can't stall the caller
+    for _ in 0..366 {
+        let date = next.naive_local().date();
+
+        if !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !holidays.contains(&date) {
+            return next;
+        }
+
+        match add_wallclock(&next, 86400) {
+            Some(following) => next = following,
+            None => break,
+        }
+    }
+
+    time
+}
+
+/// Move a time forward by `days` business days, one at a time, skipping weekends and `holidays`.
+/// Holidays are dates in the time's own timezone.
+pub fn add_business_days<Z: TimeZone>(
+    time: &DateTime<Z>,
+    days: u32,
+    holidays: &[NaiveDate],
+) -> Option<DateTime<Z>> {
+    let mut next = time.clone();
+
+    for _ in 0..days {
+        next = next_business_day(add_wallclock(&next, 86400)?, holidays);
+    }
+
+    Some(next)
+}
+
+/// The holiday dates of a guild, skipped by reminders repeating on weekdays.
+pub async fn fetch_holidays(
+    pool: impl Executor<'_, Database = Database>,
+    guild_id: u64,
+) -> Vec<NaiveDate> {
+    match sqlx::query!(
+        "
+SELECT guild_holidays.`date` FROM guild_holidays
+INNER JOIN guilds ON guilds.`id` = guild_holidays.`guild_id`
+WHERE guilds.`guild` = ?
+        ",
+        guild_id
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(rows) => rows.into_iter().map(|row| row.date).collect(),
+
+        Err(e) => {
+            warn!("Could not fetch holidays of guild {}: {:?}", guild_id, e);
+
+            vec![]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+    use chrono_tz::America::New_York;
+
+    use super::*;
+
+    fn local(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn new_york(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Tz> {
+        New_York.from_local_datetime(&local(year, month, day, hour, 0)).single().unwrap()
+    }
+
+    #[test]
+    fn resolve_local_skipped_time_moves_forward() {
+        // 2023-03-12 02:00 EST jumps to 03:00 EDT
+        let resolved = resolve_local(&New_York, local(2023, 3, 12, 2, 30)).unwrap();
+
+        assert_eq!(resolved.naive_local(), local(2023, 3, 12, 3, 30));
+        assert_eq!(resolved.with_timezone(&Utc).naive_utc(), local(2023, 3, 12, 7, 30));
+    }
+
+    #[test]
+    fn resolve_local_ambiguous_time_takes_earlier() {
+        // 2023-11-05 02:00 EDT falls back to 01:00 EST
+        let resolved = resolve_local(&New_York, local(2023, 11, 5, 1, 30)).unwrap();
+
+        assert_eq!(resolved.naive_local(), local(2023, 11, 5, 1, 30));
+        assert_eq!(resolved.with_timezone(&Utc).naive_utc(), local(2023, 11, 5, 5, 30));
+    }
+
+    #[test]
+    fn add_wallclock_keeps_time_over_spring_forward() {
+        let time = new_york(2023, 3, 11, 9);
+        let next = add_wallclock(&time, 86400).unwrap();
+
+        assert_eq!(next.naive_local(), local(2023, 3, 12, 9, 0));
+        assert_eq!((next - time).num_hours(), 23);
+    }
+
+    #[test]
+    fn add_wallclock_keeps_time_over_fall_back() {
+        let time = new_york(2023, 11, 4, 9);
+        let next = add_wallclock(&time, 86400).unwrap();
+
+        assert_eq!(next.naive_local(), local(2023, 11, 5, 9, 0));
+        assert_eq!((next - time).num_hours(), 25);
+    }
+
+    #[test]
+    fn add_wallclock_skipped_time_moves_forward() {
+        let next = add_wallclock(&new_york(2023, 3, 11, 2), 86400).unwrap();
+
+        assert_eq!(next.naive_local(), local(2023, 3, 12, 3, 0));
+    }
+
+    #[test]
+    fn add_interval_daily_keeps_time_over_dst() {
+        let mut time = new_york(2023, 3, 10, 9);
+
+        // daily from before spring-forward until after fall-back
+        for _ in 0..250 {
+            time = add_interval(&time, 86400, true).unwrap();
+
+            assert_eq!(time.hour(), 9);
+        }
+
+        assert!(time > new_york(2023, 11, 6, 0));
+    }
+
+    #[test]
+    fn add_interval_wallclock_keeps_time_over_dst() {
+        let next = add_interval(&new_york(2023, 3, 11, 20), 12 * 3600, true).unwrap();
+        assert_eq!(next.naive_local(), local(2023, 3, 12, 8, 0));
+
+        let next = add_interval(&new_york(2023, 11, 4, 20), 12 * 3600, true).unwrap();
+        assert_eq!(next.naive_local(), local(2023, 11, 5, 8, 0));
+    }
+
+    #[test]
+    fn add_interval_steps_exact_seconds_without_wallclock() {
+        let time = new_york(2023, 3, 11, 20);
+        let next = add_interval(&time, 12 * 3600, false).unwrap();
+
+        assert_eq!((next - time).num_seconds(), 12 * 3600);
+        assert_eq!(next.naive_local(), local(2023, 3, 12, 9, 0));
+
+        let time = new_york(2023, 11, 4, 20);
+        let next = add_interval(&time, 12 * 3600, false).unwrap();
+
+        assert_eq!((next - time).num_seconds(), 12 * 3600);
+        assert_eq!(next.naive_local(), local(2023, 11, 5, 7, 0));
+    }
+
+    #[test]
+    fn next_business_day_keeps_time_over_fall_back() {
+        // Saturday before the change, moving past Sunday 2023-11-05
+        let next = next_business_day(new_york(2023, 11, 4, 9), &[]);
+
+        assert_eq!(next.naive_local(), local(2023, 11, 6, 9, 0));
+    }
+
+    #[test]
+    fn add_business_days_skips_weekends() {
+        // Thursday
+        let time = new_york(2023, 11, 2, 9);
+
+        let next = add_business_days(&time, 2, &[]).unwrap();
+        assert_eq!(next.naive_local(), local(2023, 11, 6, 9, 0));
+
+        let next = add_business_days(&time, 5, &[]).unwrap();
+        assert_eq!(next.naive_local(), local(2023, 11, 9, 9, 0));
+    }
+
+    #[test]
+    fn add_business_days_skips_holidays() {
+        let holidays = [NaiveDate::from_ymd_opt(2023, 11, 6).unwrap()];
+        let next = add_business_days(&new_york(2023, 11, 2, 9), 2, &holidays).unwrap();
+
+        assert_eq!(next.naive_local(), local(2023, 11, 7, 9, 0));
+    }
+
+    #[test]
+    fn validate_rrule_rejects_large_interval() {
+        assert!(validate_rrule("FREQ=YEARLY;INTERVAL=4000000000;BYMONTH=2;BYMONTHDAY=29").is_err());
+        assert!(validate_rrule("FREQ=YEARLY;INTERVAL=4;BYMONTH=2;BYMONTHDAY=29").is_ok());
+    }
+
+    #[test]
+    fn validate_rrule_rejects_rule_without_dates() {
+        assert!(validate_rrule("FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30").is_err());
+        assert!(validate_rrule("FREQ=MONTHLY;BYMONTH=4,6,9,11;BYMONTHDAY=31").is_err());
+        assert!(validate_rrule("FREQ=MONTHLY;BYDAY=5FR").is_ok());
+    }
+
+    #[test]
+    fn following_steps_by_interval() {
+        let rule = parse_rrule("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR").unwrap();
+        let monday = Utc.from_utc_datetime(&local(2024, 1, 1, 9, 0));
+
+        let next = rule.following(&monday, false).unwrap();
+        assert_eq!(next.naive_utc(), local(2024, 1, 5, 9, 0));
+
+        let next = rule.following(&next, false).unwrap();
+        assert_eq!(next.naive_utc(), local(2024, 1, 15, 9, 0));
+
+        assert_eq!(rule.following(&monday, true), Some(monday));
+    }
+
+    #[test]
+    fn following_finds_leap_day_with_large_interval() {
+        let rule = parse_rrule("FREQ=YEARLY;INTERVAL=366;BYMONTH=2;BYMONTHDAY=29").unwrap();
+        let time = Utc.from_utc_datetime(&local(2024, 3, 1, 9, 0));
+
+        // 2390 isn't a leap year, 2756 is
+        let next = rule.following(&time, false).unwrap();
+        assert_eq!(next.naive_utc(), local(2756, 2, 29, 9, 0));
+    }
+
+    #[test]
+    fn shortest_interval_in_timezone() {
+        let schedule = parse_cron("0 9 * * *").unwrap();
+
+        // a day, or an hour less across a daylight saving change
+        let shortest = shortest_interval(&schedule, New_York).unwrap();
+        assert!(shortest == 86400 || shortest == 82800);
+    }
+
+    #[test]
+    fn following_stops_at_end_of_calendar() {
+        let rule = parse_rrule("FREQ=YEARLY;INTERVAL=366;BYMONTH=2;BYMONTHDAY=29").unwrap();
+        let time = Utc.from_utc_datetime(&NaiveDate::MAX.and_hms_opt(0, 0, 0).unwrap());
+
+        assert_eq!(rule.following(&time, false), None);
+    }
+}
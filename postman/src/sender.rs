@@ -1,103 +1,69 @@
-use chrono::{DateTime, Days, Duration, Months, TimeZone};
+use std::{env, mem::discriminant};
+
+use chrono::{DateTime, Duration, Months};
 use chrono_tz::Tz;
 use lazy_static::lazy_static;
 use log::{error, info, warn};
-use num_integer::Integer;
-use regex::{Captures, Regex};
+use rand::Rng;
 use serde::Deserialize;
 use serenity::{
-    builder::CreateEmbed,
+    builder::{CreateAllowedMentions, CreateComponents, CreateEmbed, ParseValue},
     http::{CacheHttp, Http, HttpError, StatusCode},
+    json::Value,
     model::{
-        channel::{Channel, Embed as SerenityEmbed},
-        id::ChannelId,
+        application::component::ButtonStyle,
+        channel::{Channel, ChannelType, Embed as SerenityEmbed, Message},
+        id::{ChannelId, MessageId, RoleId, UserId},
         webhook::Webhook,
     },
     Error, Result,
 };
 use sqlx::{
     types::{
-        chrono::{NaiveDateTime, Utc},
+        chrono::{NaiveDate, NaiveDateTime, Utc},
         Json,
     },
     Executor,
 };
 
-use crate::Database;
-
-lazy_static! {
-    pub static ref TIMEFROM_REGEX: Regex =
-        Regex::new(r#"<<timefrom:(?P<time>\d+):(?P<format>.+)?>>"#).unwrap();
-    pub static ref TIMENOW_REGEX: Regex =
-        Regex::new(r#"<<timenow:(?P<timezone>(?:\w|/|_)+):(?P<format>.+)?>>"#).unwrap();
-}
-
-fn fmt_displacement(format: &str, seconds: u64) -> String {
-    let mut seconds = seconds;
-    let mut days: u64 = 0;
-    let mut hours: u64 = 0;
-    let mut minutes: u64 = 0;
-
-    for (rep, time_type, div) in
-        [("%d", &mut days, 86400), ("%h", &mut hours, 3600), ("%m", &mut minutes, 60)].iter_mut()
-    {
-        if format.contains(*rep) {
-            let (divided, new_seconds) = seconds.div_rem(&div);
-
-            **time_type = divided;
-            seconds = new_seconds;
-        }
-    }
-
-    format
-        .replace("%s", &seconds.to_string())
-        .replace("%m", &minutes.to_string())
-        .replace("%h", &hours.to_string())
-        .replace("%d", &days.to_string())
-}
-
-pub fn substitute(string: &str) -> String {
-    let new = TIMEFROM_REGEX.replace(string, |caps: &Captures| {
-        let final_time = caps.name("time").map(|m| m.as_str().parse::<i64>().ok()).flatten();
-        let format = caps.name("format").map(|m| m.as_str());
-
-        if let (Some(final_time), Some(format)) = (final_time, format) {
-            match NaiveDateTime::from_timestamp_opt(final_time, 0) {
-                Some(dt) => {
-                    let now = Utc::now().naive_utc();
-
-                    let difference = {
-                        if now < dt {
-                            dt - Utc::now().naive_utc()
-                        } else {
-                            Utc::now().naive_utc() - dt
-                        }
-                    };
-
-                    fmt_displacement(format, difference.num_seconds() as u64)
-                }
+use crate::{
+    components::{AcknowledgeReminder, ComponentDataModel, SnoozeReminder},
+    schedule::{
+        add_business_days, add_interval, add_months_anchored, next_business_day, parse_cron,
+        parse_rrule,
+    },
+    template::{parse_template, TemplateContext},
+    Database,
+};
 
-                None => String::new(),
-            }
-        } else {
-            String::new()
-        }
-    });
+/// Discord allows at most 10 embeds and 2000 characters per message
+const MAX_DIGEST_SIZE: usize = 10;
+const MAX_EMBEDS: usize = 10;
+const MAX_CONTENT_LENGTH: usize = 2000;
+const MAX_THREAD_TITLE_LENGTH: usize = 100;
 
-    TIMENOW_REGEX
-        .replace(&new, |caps: &Captures| {
-            let timezone = caps.name("timezone").map(|m| m.as_str().parse::<Tz>().ok()).flatten();
-            let format = caps.name("format").map(|m| m.as_str());
+/// Labels and lengths in minutes of the snooze buttons
+const SNOOZE_TIMES: [(&str, u32); 3] = [("Snooze 10m", 10), ("Snooze 1h", 60), ("Tomorrow", 1440)];
 
-            if let (Some(timezone), Some(format)) = (timezone, format) {
-                let now = Utc::now().with_timezone(&timezone);
+/// Delay before the first retry of a failed delivery. Doubles on each further attempt
+const RETRY_BASE_DELAY: u64 = 30;
 
-                now.format(format).to_string()
-            } else {
-                String::new()
-            }
-        })
-        .to_string()
+lazy_static! {
+    pub static ref MAX_ATTEMPTS: u32 = env::var("REMIND_MAX_ATTEMPTS")
+        .ok()
+        .map(|inner| inner.parse::<u32>().ok())
+        .flatten()
+        .unwrap_or(5);
+    pub static ref MISSED_GRACE: i64 = env::var("MISSED_GRACE")
+        .ok()
+        .map(|inner| inner.parse::<i64>().ok())
+        .flatten()
+        .unwrap_or(300);
+    pub static ref REMIND_LEASE: u64 = env::var("REMIND_LEASE")
+        .ok()
+        .map(|inner| inner.parse::<u64>().ok())
+        .flatten()
+        .unwrap_or(120);
 }
 
 struct Embed {
@@ -123,7 +89,7 @@ struct EmbedField {
 impl Embed {
     pub async fn from_id(
         pool: impl Executor<'_, Database = Database> + Copy,
-        id: u32,
+        reminder: &Reminder,
     ) -> Option<Self> {
         match sqlx::query_as!(
             Self,
@@ -141,20 +107,13 @@ impl Embed {
              IFNULL(`embed_fields`, '[]') AS "fields:_"
             FROM reminders
             WHERE `id` = ?"#,
-            id
+            reminder.id
         )
         .fetch_one(pool)
         .await
         {
-            Ok(mut embed) => {
-                embed.title = substitute(&embed.title);
-                embed.description = substitute(&embed.description);
-                embed.footer = substitute(&embed.footer);
-
-                embed.fields.iter_mut().for_each(|mut field| {
-                    field.title = substitute(&field.title);
-                    field.value = substitute(&field.value);
-                });
+            Ok(embed) => {
+                let embed = embed.render(reminder);
 
                 if embed.has_content() {
                     Some(embed)
@@ -171,6 +130,64 @@ impl Embed {
         }
     }
 
+    /// The reminder's embeds from `reminder_embeds`, sent after the one stored with the reminder.
+    pub async fn extras(
+        pool: impl Executor<'_, Database = Database> + Copy,
+        reminder: &Reminder,
+    ) -> Vec<Self> {
+        if reminder.extra_embeds == 0 {
+            return vec![];
+        }
+
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+             `title`,
+             `description`,
+             `image_url`,
+             `thumbnail_url`,
+             `footer`,
+             `footer_url`,
+             `author`,
+             `author_url`,
+             `color`,
+             IFNULL(`fields`, '[]') AS "fields:_"
+            FROM reminder_embeds
+            WHERE `reminder_id` = ?
+            ORDER BY `id`"#,
+            reminder.id
+        )
+        .fetch_all(pool)
+        .await
+        {
+            Ok(embeds) => embeds
+                .into_iter()
+                .map(|embed| embed.render(reminder))
+                .filter(|embed| embed.has_content())
+                .collect(),
+
+            Err(e) => {
+                warn!("Error loading embeds of reminder: {:?}", e);
+
+                vec![]
+            }
+        }
+    }
+
+    fn render(mut self, reminder: &Reminder) -> Self {
+        self.title = reminder.render(&self.title);
+        self.description = reminder.render(&self.description);
+        self.footer = reminder.render(&self.footer);
+
+        self.fields.iter_mut().for_each(|mut field| {
+            field.title = reminder.render(&field.title);
+            field.value = reminder.render(&field.value);
+        });
+
+        self
+    }
+
     pub fn has_content(&self) -> bool {
         if self.title.is_empty()
             && self.description.is_empty()
@@ -231,22 +248,112 @@ impl Into<CreateEmbed> for Embed {
     }
 }
 
+/// A file sent with a reminder
+struct Attachment {
+    name: String,
+    data: Vec<u8>,
+}
+
+impl Attachment {
+    /// The reminder's own attachment, followed by any from `reminder_attachments`.
+    async fn for_reminder(
+        pool: impl Executor<'_, Database = Database> + Copy,
+        reminder: &Reminder,
+    ) -> Vec<Self> {
+        let mut attachments = vec![];
+
+        if let (Some(data), Some(name)) = (&reminder.attachment, &reminder.attachment_name) {
+            attachments.push(Attachment { name: name.clone(), data: data.clone() });
+        }
+
+        if reminder.extra_attachments > 0 {
+            match sqlx::query_as!(
+                Self,
+                "SELECT `name`, `data` FROM reminder_attachments WHERE `reminder_id` = ? \
+                ORDER BY `id`",
+                reminder.id
+            )
+            .fetch_all(pool)
+            .await
+            {
+                Ok(extras) => attachments.extend(extras),
+
+                Err(e) => {
+                    warn!("Error loading attachments of reminder: {:?}", e);
+                }
+            }
+        }
+
+        attachments
+    }
+}
+
+/// Discord's allowed mentions object, as stored with a reminder
+#[derive(Clone, Default, Deserialize)]
+struct AllowedMentions {
+    #[serde(default)]
+    parse: Vec<ParseValue>,
+    #[serde(default)]
+    users: Vec<String>,
+    #[serde(default)]
+    roles: Vec<String>,
+}
+
+// serenity's `ParseValue` isn't `PartialEq`, so compare its variants
+impl PartialEq for AllowedMentions {
+    fn eq(&self, other: &Self) -> bool {
+        self.parse.len() == other.parse.len()
+            && self.parse.iter().zip(&other.parse).all(|(a, b)| discriminant(a) == discriminant(b))
+            && self.users == other.users
+            && self.roles == other.roles
+    }
+}
+
+#[derive(Clone, Copy)]
+enum DeliveryMethod {
+    Webhook,
+    Channel,
+}
+
+impl DeliveryMethod {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryMethod::Webhook => "webhook",
+            DeliveryMethod::Channel => "channel",
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Reminder {
     id: u32,
+    uid: String,
 
-    channel_id: u64,
+    pub channel_id: u64,
     webhook_id: Option<u64>,
     webhook_token: Option<String>,
 
     channel_paused: bool,
     channel_paused_until: Option<NaiveDateTime>,
+    pub channel_digest: bool,
     enabled: bool,
+    locked_by: Option<String>,
+
+    set_by: Option<u64>,
+    guild_id: Option<u64>,
+    guild_log_channel: Option<u64>,
+    guild_name: Option<String>,
 
     tts: bool,
     pin: bool,
     content: String,
+    content_pool: Option<Json<Vec<String>>>,
+    content_pool_mode: String,
+    content_pool_cursor: u32,
     attachment: Option<Vec<u8>>,
     attachment_name: Option<String>,
+    extra_attachments: i64,
+    extra_embeds: i64,
 
     utc_time: DateTime<Utc>,
     timezone: String,
@@ -254,18 +361,81 @@ pub struct Reminder {
     expires: Option<DateTime<Utc>>,
     interval_seconds: Option<u32>,
     interval_months: Option<u32>,
+    interval_weekdays: bool,
+    interval_wallclock: bool,
+    month_anchor: Option<String>,
+    month_anchor_day: Option<u32>,
+    cron: Option<String>,
+    rrule: Option<String>,
+    occurrences: u32,
+    sent_count: u32,
+    max_occurrences: Option<u32>,
+    exclude_dates: Option<Json<Vec<String>>>,
+    holidays: Option<Json<Vec<String>>>,
+    skip_next: bool,
+    retry_count: u32,
+    missed_policy: String,
+    replace_mode: Option<String>,
+    last_message_id: Option<u64>,
+    delete_after: Option<u32>,
+    thread_id: Option<u64>,
+    thread_title: Option<String>,
+    ack_button: bool,
+    snooze_buttons: bool,
+    escalate_after: Option<u32>,
+    escalate_mentions: Option<String>,
+    escalate_user: Option<u64>,
+    escalate_limit: u32,
+    allowed_mentions: Option<Json<AllowedMentions>>,
 
     avatar: Option<String>,
     username: Option<String>,
 }
 
 impl Reminder {
-    pub async fn fetch_reminders(pool: impl Executor<'_, Database = Database> + Copy) -> Vec<Self> {
+    /// Claim due reminders for this instance and fetch them. Reminders leased to another instance
+    /// are skipped until their lease runs out, so several postmen can share the table.
+    pub async fn fetch_reminders(
+        pool: impl Executor<'_, Database = Database> + Copy,
+        instance: &str,
+        lease: u64,
+    ) -> Vec<Self> {
+        if let Err(e) = sqlx::query!(
+            "
+UPDATE
+    reminders
+SET
+    `locked_by` = ?,
+    `locked_until` = DATE_ADD(NOW(), INTERVAL ? SECOND)
+WHERE
+    `failed` = 0
+    AND `due_at` <= NOW()
+    AND (
+        `interval_seconds` IS NOT NULL
+        OR `interval_months` IS NOT NULL
+        OR `cron` IS NOT NULL
+        OR `rrule` IS NOT NULL
+        OR enabled
+    )
+    AND (`locked_until` IS NULL OR `locked_until` < NOW())
+            ",
+            instance,
+            lease
+        )
+        .execute(pool)
+        .await
+        {
+            warn!("Could not claim reminders: {:?}", e);
+
+            return vec![];
+        }
+
         match sqlx::query_as_unchecked!(
             Reminder,
             r#"
 SELECT
     reminders.`id` AS id,
+    reminders.`uid` AS uid,
 
     channels.`channel` AS channel_id,
     channels.`webhook_id` AS webhook_id,
@@ -273,13 +443,29 @@ SELECT
 
     channels.`paused` AS 'channel_paused',
     channels.`paused_until` AS 'channel_paused_until',
+    channels.`digest` AS 'channel_digest',
     reminders.`enabled` AS 'enabled',
+    reminders.`locked_by` AS 'locked_by',
+
+    users.`user` AS 'set_by',
+    guilds.`guild` AS 'guild_id',
+    guilds.`log_channel` AS 'guild_log_channel',
+    guilds.`name` AS 'guild_name',
 
     reminders.`tts` AS tts,
     reminders.`pin` AS pin,
     reminders.`content` AS content,
+    reminders.`content_pool` AS 'content_pool',
+    reminders.`content_pool_mode` AS 'content_pool_mode',
+    reminders.`content_pool_cursor` AS 'content_pool_cursor',
     reminders.`attachment` AS attachment,
     reminders.`attachment_name` AS attachment_name,
+    (
+        SELECT COUNT(*) FROM reminder_attachments WHERE reminder_attachments.`reminder_id` = reminders.`id`
+    ) AS 'extra_attachments',
+    (
+        SELECT COUNT(*) FROM reminder_embeds WHERE reminder_embeds.`reminder_id` = reminders.`id`
+    ) AS 'extra_embeds',
 
     reminders.`utc_time` AS 'utc_time',
     reminders.`timezone` AS timezone,
@@ -287,6 +473,35 @@ SELECT
     reminders.`expires` AS 'expires',
     reminders.`interval_seconds` AS 'interval_seconds',
     reminders.`interval_months` AS 'interval_months',
+    reminders.`interval_weekdays` AS 'interval_weekdays',
+    reminders.`interval_wallclock` AS 'interval_wallclock',
+    reminders.`month_anchor` AS 'month_anchor',
+    reminders.`month_anchor_day` AS 'month_anchor_day',
+    reminders.`cron` AS 'cron',
+    reminders.`rrule` AS 'rrule',
+    reminders.`occurrences` AS 'occurrences',
+    reminders.`sent_count` AS 'sent_count',
+    reminders.`max_occurrences` AS 'max_occurrences',
+    reminders.`exclude_dates` AS 'exclude_dates',
+    (
+        SELECT JSON_ARRAYAGG(guild_holidays.`date`) FROM guild_holidays
+        WHERE guild_holidays.`guild_id` = guilds.`id` AND reminders.`interval_weekdays`
+    ) AS 'holidays',
+    reminders.`skip_next` AS 'skip_next',
+    reminders.`retry_count` AS 'retry_count',
+    IFNULL(reminders.`missed_policy`, IFNULL(guilds.`missed_policy`, 'once')) AS 'missed_policy',
+    reminders.`replace_mode` AS 'replace_mode',
+    reminders.`last_message_id` AS 'last_message_id',
+    reminders.`delete_after` AS 'delete_after',
+    reminders.`thread_id` AS 'thread_id',
+    reminders.`thread_title` AS 'thread_title',
+    reminders.`ack_button` AS 'ack_button',
+    reminders.`snooze_buttons` AS 'snooze_buttons',
+    reminders.`escalate_after` AS 'escalate_after',
+    reminders.`escalate_mentions` AS 'escalate_mentions',
+    reminders.`escalate_user` AS 'escalate_user',
+    reminders.`escalate_limit` AS 'escalate_limit',
+    reminders.`allowed_mentions` AS 'allowed_mentions',
 
     reminders.`avatar` AS avatar,
     reminders.`username` AS username
@@ -296,22 +511,23 @@ INNER JOIN
     channels
 ON
     reminders.channel_id = channels.id
+LEFT JOIN
+    users
+ON
+    reminders.set_by = users.id
+LEFT JOIN
+    guilds
+ON
+    channels.guild_id = guilds.id
 WHERE
-    reminders.`id` IN (
-        SELECT
-            MIN(id)
-        FROM
-            reminders
-        WHERE
-            reminders.`utc_time` <= NOW()
-            AND (
-                reminders.`interval_seconds` IS NOT NULL
-                OR reminders.`interval_months` IS NOT NULL
-                OR reminders.enabled
-            )
-        GROUP BY channel_id
-    )
+    reminders.`locked_by` = ?
+    AND reminders.`locked_until` >= NOW()
+    AND reminders.`failed` = 0
+    AND reminders.`due_at` <= NOW()
+ORDER BY
+    reminders.`utc_time`, reminders.`id`
     "#,
+            instance
         )
         .fetch_all(pool)
         .await
@@ -319,7 +535,8 @@ WHERE
             Ok(reminders) => reminders
                 .into_iter()
                 .map(|mut rem| {
-                    rem.content = substitute(&rem.content);
+                    rem.content = rem.render(&rem.content);
+                    rem.thread_title = rem.thread_title.as_ref().map(|title| rem.render(title));
 
                     rem
                 })
@@ -333,6 +550,98 @@ WHERE
         }
     }
 
+    /// Times of reminders and message deletions due within the next `seconds` seconds, used to
+    /// schedule wakeups.
+    pub async fn fetch_upcoming(
+        pool: impl Executor<'_, Database = Database> + Copy,
+        seconds: u64,
+    ) -> Vec<NaiveDateTime> {
+        match sqlx::query!(
+            "
+SELECT
+    reminders.`due_at` AS 'utc_time'
+FROM
+    reminders
+WHERE
+    reminders.`failed` = 0
+    AND reminders.`due_at` <= DATE_ADD(NOW(), INTERVAL ? SECOND)
+    AND (reminders.`locked_until` IS NULL OR reminders.`locked_until` < NOW())
+    AND (
+        reminders.`interval_seconds` IS NOT NULL
+        OR reminders.`interval_months` IS NOT NULL
+        OR reminders.`cron` IS NOT NULL
+        OR reminders.`rrule` IS NOT NULL
+        OR reminders.enabled
+    )
+UNION ALL
+SELECT
+    pending_deletions.`delete_at` AS 'utc_time'
+FROM
+    pending_deletions
+WHERE
+    pending_deletions.`delete_at` <= DATE_ADD(NOW(), INTERVAL ? SECOND)
+UNION ALL
+SELECT
+    pending_escalations.`escalate_at` AS 'utc_time'
+FROM
+    pending_escalations
+WHERE
+    pending_escalations.`escalate_at` <= DATE_ADD(NOW(), INTERVAL ? SECOND)
+            ",
+            seconds,
+            seconds,
+            seconds
+        )
+        .fetch_all(pool)
+        .await
+        {
+            Ok(rows) => rows.into_iter().map(|row| row.utc_time).collect(),
+
+            Err(e) => {
+                warn!("Could not fetch upcoming reminders: {:?}", e);
+
+                vec![]
+            }
+        }
+    }
+
+    /// Fill in a template from the reminder's content, embed or thread title. Templates are
+    /// checked as reminders are created, so one that fails to parse (e.g. from before tags were
+    /// checked) is sent as it is.
+    fn render(&self, string: &str) -> String {
+        match parse_template(string) {
+            Ok(template) => template.render(&self.template_context()),
+
+            Err(e) => {
+                warn!("Reminder {} has an invalid template: {}", self.id, e);
+
+                string.to_string()
+            }
+        }
+    }
+
+    fn template_context(&self) -> TemplateContext {
+        let timezone = self.timezone.parse().unwrap_or(Tz::UTC);
+
+        let next = self.next_scheduled(&self.utc_time.with_timezone(&timezone), &self.holidays());
+
+        TemplateContext {
+            channel: Some(self.target_channel().0),
+            guild: self.guild_name.clone(),
+            creator: self.set_by,
+            occurrence: self.occurrences + 1,
+            max_occurrences: self.max_occurrences,
+            next: next.map(|next| next.with_timezone(&Utc)),
+            now: Utc::now(),
+        }
+    }
+
+    /// The channel messages are posted in: the reminder's thread if it is set to post in one,
+    /// otherwise its own channel.
+    fn target_channel(&self) -> ChannelId {
+        ChannelId(self.thread_id.unwrap_or(self.channel_id))
+    }
+
     async fn reset_webhook(&self, pool: impl Executor<'_, Database = Database> + Copy) {
         let _ = sqlx::query!(
             "UPDATE channels SET webhook_id = NULL, webhook_token = NULL WHERE channel = ?",
@@ -342,184 +651,887 @@ WHERE
         .await;
     }
 
-    async fn refresh(&self, pool: impl Executor<'_, Database = Database> + Copy) {
-        if self.interval_seconds.is_some() || self.interval_months.is_some() {
-            let now = Utc::now();
-            let mut updated_reminder_time =
-                self.utc_time.with_timezone(&self.timezone.parse().unwrap_or(Tz::UTC));
+    /// Whether this reminder is being sent well after it was due, e.g. because the bot was down.
+    /// Retries are late by design, so they don't count.
+    fn is_late(&self) -> bool {
+        self.retry_count == 0 && Utc::now() - self.utc_time > Duration::seconds(*MISSED_GRACE)
+    }
 
-            if let Some(interval) = self.interval_months {
-                updated_reminder_time = updated_reminder_time
-                    .checked_add_months(Months::new(interval))
-                    .unwrap_or_else(|| {
-                        warn!("Could not add months to a reminder");
+    fn is_repeating(&self) -> bool {
+        self.interval_seconds.is_some()
+            || self.interval_months.is_some()
+            || self.cron.is_some()
+            || self.rrule.is_some()
+    }
 
-                        updated_reminder_time
-                    });
-            }
+    /// The occurrence following `time`. `None` if the reminder doesn't repeat, or if its interval
+    /// doesn't move the time forward.
+    fn next_occurrence(&self, time: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        if let Some(cron) = &self.cron {
+            return match parse_cron(cron) {
+                Ok(schedule) => schedule.after(time).next(),
+
+                Err(e) => {
+                    warn!("Reminder {} has an invalid cron expression: {}", self.id, e);
 
-            fn increment_days<T: TimeZone>(
-                now: DateTime<Utc>,
-                mut new_time: DateTime<T>,
-                interval: u32,
-            ) -> Option<DateTime<T>> {
-                while new_time < now {
-                    new_time = new_time.checked_add_days(Days::new((interval / 86400).into()))?;
+                    None
                 }
+            };
+        }
 
-                Some(new_time)
-            }
+        if let Some(rrule) = &self.rrule {
+            return match parse_rrule(rrule) {
+                Ok(rule) => rule.following(time, false),
 
-            if let Some(interval) = self.interval_seconds {
-                if interval.div_rem(&86400).1 == 0 {
-                    updated_reminder_time =
-                        match increment_days(now, updated_reminder_time, interval) {
-                            Some(d) => d,
-                            None => {
-                                warn!("Failed to update days on a reminder.");
-                                updated_reminder_time
-                            }
-                        }
+                Err(e) => {
+                    warn!("Reminder {} has an invalid recurrence rule: {}", self.id, e);
+
+                    None
                 }
+            };
+        }
+
+        if !self.is_repeating() {
+            return None;
+        }
 
-                while updated_reminder_time < now {
-                    updated_reminder_time += Duration::seconds(interval as i64);
+        let mut next = time.clone();
+
+        if let Some(interval) = self.interval_months {
+            next = match &self.month_anchor {
+                Some(anchor) => {
+                    add_months_anchored(&next, interval, anchor, self.month_anchor_day)?
                 }
-            }
+                None => next.checked_add_months(Months::new(interval))?,
+            };
+        }
 
-            if self.expires.map_or(false, |expires| updated_reminder_time > expires) {
-                self.force_delete(pool).await;
-            } else {
-                sqlx::query!(
-                    "UPDATE reminders SET `utc_time` = ? WHERE `id` = ?",
-                    updated_reminder_time.with_timezone(&Utc),
-                    self.id
-                )
-                .execute(pool)
-                .await
-                .expect(&format!("Could not update time on Reminder {}", self.id));
-            }
+        if let Some(interval) = self.interval_seconds {
+            next = add_interval(&next, interval as i64, self.interval_wallclock)?;
+        }
+
+        if &next > time {
+            Some(next)
         } else {
-            self.force_delete(pool).await;
+            None
         }
     }
 
-    async fn force_delete(&self, pool: impl Executor<'_, Database = Database> + Copy) {
-        sqlx::query!("DELETE FROM reminders WHERE `id` = ?", self.id)
-            .execute(pool)
-            .await
-            .expect(&format!("Could not delete Reminder {}", self.id));
+    /// The occurrence following `time`, moved past weekends and `holidays` for reminders that
+    /// repeat on weekdays. Intervals of whole days count that many business days; others move to
+    /// the first business day after the interval.
+    fn next_scheduled(&self, time: &DateTime<Tz>, holidays: &[NaiveDate]) -> Option<DateTime<Tz>> {
+        if !self.interval_weekdays {
+            return self.next_occurrence(time);
+        }
+
+        match self.interval_seconds {
+            Some(seconds)
+                if seconds > 0
+                    && seconds % 86400 == 0
+                    && self.interval_months.is_none()
+                    && self.cron.is_none()
+                    && self.rrule.is_none() =>
+            {
+                add_business_days(time, seconds / 86400, holidays)
+            }
+
+            _ => self.next_occurrence(time).map(|next| next_business_day(next, holidays)),
+        }
     }
 
-    async fn pin_message<M: Into<u64>>(&self, message_id: M, http: impl AsRef<Http>) {
-        let _ = http.as_ref().pin_message(self.channel_id, message_id.into(), None).await;
+    /// The guild's holidays, loaded for reminders that repeat on weekdays.
+    fn holidays(&self) -> Vec<NaiveDate> {
+        self.holidays.as_ref().map_or(vec![], |dates| {
+            dates
+                .iter()
+                .filter_map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+                .collect()
+        })
     }
 
-    pub async fn send(
+    /// Move a repeating reminder on to its next occurrence, or remove it. `delivered` is whether
+    /// this occurrence was sent, which counts towards `max_occurrences`.
+    async fn refresh(
         &self,
         pool: impl Executor<'_, Database = Database> + Copy,
-        cache_http: impl CacheHttp,
+        http: &Http,
+        delivered: bool,
     ) {
-        async fn send_to_channel(
-            cache_http: impl CacheHttp,
-            reminder: &Reminder,
-            embed: Option<CreateEmbed>,
-        ) -> Result<()> {
-            let channel = ChannelId(reminder.channel_id).to_channel(&cache_http).await;
+        if self.is_repeating() {
+            let now = Utc::now();
+            let mut updated_reminder_time =
+                self.utc_time.with_timezone(&self.timezone.parse().unwrap_or(Tz::UTC));
 
-            match channel {
-                Ok(Channel::Guild(channel)) => {
-                    match channel
-                        .send_message(&cache_http, |m| {
-                            m.content(&reminder.content).tts(reminder.tts);
+            let mut occurrences = self.occurrences;
+            let sent_count = self.sent_count + delivered as u32;
 
-                            if let (Some(attachment), Some(name)) =
-                                (&reminder.attachment, &reminder.attachment_name)
-                            {
-                                m.add_file((attachment as &[u8], name.as_str()));
-                            }
+            let holidays = self.holidays();
 
-                            if let Some(embed) = embed {
-                                m.set_embed(embed);
-                            }
+            // when sending every missed occurrence, only step to the next one
+            let catch_up = self.missed_policy == "all" && self.is_late();
 
-                            m
-                        })
-                        .await
-                    {
-                        Ok(m) => {
-                            if reminder.pin {
-                                reminder.pin_message(m.id, cache_http.http()).await;
-                            }
+            loop {
+                match self.next_scheduled(&updated_reminder_time, &holidays) {
+                    Some(next) => {
+                        updated_reminder_time = next;
+                        occurrences += 1;
+                    }
 
-                            Ok(())
-                        }
-                        Err(e) => Err(e),
+                    None => {
+                        warn!("Could not find next occurrence of Reminder {}", self.id);
+
+                        self.mark_failed(pool).await;
+                        return;
                     }
                 }
-                Ok(Channel::Private(channel)) => {
-                    match channel
-                        .send_message(&cache_http.http(), |m| {
-                            m.content(&reminder.content).tts(reminder.tts);
 
-                            if let (Some(attachment), Some(name)) =
-                                (&reminder.attachment, &reminder.attachment_name)
-                            {
-                                m.add_file((attachment as &[u8], name.as_str()));
-                            }
+                if self.is_excluded(&updated_reminder_time) {
+                    continue;
+                }
 
-                            if let Some(embed) = embed {
-                                m.set_embed(embed);
-                            }
+                if catch_up || updated_reminder_time >= now {
+                    break;
+                }
+            }
 
-                            m
-                        })
-                        .await
-                    {
-                        Ok(m) => {
-                            if reminder.pin {
-                                reminder.pin_message(m.id, cache_http.http()).await;
-                            }
+            let rule_ended = self
+                .rrule
+                .as_ref()
+                .and_then(|rrule| parse_rrule(rrule).ok())
+                .map_or(false, |rule| rule.has_ended(&updated_reminder_time, occurrences));
 
-                            Ok(())
-                        }
-                        Err(e) => Err(e),
-                    }
-                }
-                Err(e) => Err(e),
-                _ => Err(Error::Other("Channel not of valid type")),
+            if self.expires.map_or(false, |expires| updated_reminder_time > expires) {
+                self.notify_removal(http, "it has passed its expiry time").await;
+                self.delete_finished(pool).await;
+            } else if rule_ended {
+                self.notify_removal(http, "its recurrence rule has no more occurrences").await;
+                self.delete_finished(pool).await;
+            } else if self.max_occurrences.map_or(false, |max| sent_count >= max) {
+                self.notify_removal(http, "it has been sent the number of times it was set to")
+                    .await;
+                self.delete_finished(pool).await;
+            } else {
+                sqlx::query!(
+                    "
+UPDATE reminders SET `utc_time` = ?, `occurrences` = ?, `sent_count` = ?, `skip_next` = 0, \
+                     `retry_count` = 0, `retry_at` = NULL, `locked_by` = NULL, \
+                     `locked_until` = NULL WHERE `id` = ? AND `locked_by` = ?
+                    ",
+                    updated_reminder_time.with_timezone(&Utc),
+                    occurrences,
+                    sent_count,
+                    self.id,
+                    self.locked_by
+                )
+                .execute(pool)
+                .await
+                .expect(&format!("Could not update time on Reminder {}", self.id));
             }
+        } else {
+            self.delete_finished(pool).await;
         }
+    }
 
-        async fn send_to_webhook(
-            cache_http: impl CacheHttp,
-            reminder: &Reminder,
-            webhook: Webhook,
-            embed: Option<CreateEmbed>,
-        ) -> Result<()> {
-            match webhook
-                .execute(&cache_http.http(), reminder.pin || reminder.restartable, |w| {
-                    w.content(&reminder.content).tts(reminder.tts);
+    /// Extend this instance's lease on the reminder, just before sending it. `false` if the lease
+    /// has run out and another instance has claimed the reminder since, in which case it's left
+    /// to that instance.
+    async fn renew_lease(&self, pool: impl Executor<'_, Database = Database> + Copy) -> bool {
+        match sqlx::query!(
+            "
+UPDATE reminders SET `locked_until` = DATE_ADD(NOW(), INTERVAL ? SECOND) WHERE `id` = ? AND \
+             `locked_by` = ?
+            ",
+            *REMIND_LEASE,
+            self.id,
+            self.locked_by
+        )
+        .execute(pool)
+        .await
+        {
+            Ok(result) if result.rows_affected() > 0 => true,
 
-                    if let Some(username) = &reminder.username {
-                        w.username(username);
-                    }
+            Ok(_) => {
+                info!("Reminder {} was claimed by another instance. Not sending", self.id);
 
-                    if let Some(avatar) = &reminder.avatar {
-                        w.avatar_url(avatar);
+                false
+            }
+
+            Err(e) => {
+                warn!("Could not renew lease on Reminder {}: {:?}", self.id, e);
+
+                false
+            }
+        }
+    }
+
+    async fn mark_failed(&self, pool: impl Executor<'_, Database = Database> + Copy) {
+        warn!("Marking Reminder {} as failed", self.id);
+
+        sqlx::query!(
+            "
+UPDATE reminders SET `failed` = 1, `retry_at` = NULL, `locked_by` = NULL, `locked_until` = NULL \
+             WHERE `id` = ? AND `locked_by` = ?
+            ",
+            self.id,
+            self.locked_by
+        )
+        .execute(pool)
+        .await
+        .expect(&format!("Could not mark Reminder {} as failed", self.id));
+    }
+
+    /// Schedule another attempt at a delivery that failed for a transient reason. Once the attempts
+    /// run out, repeating reminders move on to their next occurrence and others are marked failed.
+    async fn retry(&self, pool: impl Executor<'_, Database = Database> + Copy, http: &Http) {
+        if self.retry_count + 1 >= *MAX_ATTEMPTS {
+            if self.is_repeating() {
+                warn!("Giving up on this occurrence of Reminder {}", self.id);
+
+                self.refresh(pool, http, false).await;
+            } else {
+                self.mark_failed(pool).await;
+            }
+        } else {
+            let delay = retry_delay(self.retry_count);
+
+            sqlx::query!(
+                "
+UPDATE reminders SET `retry_count` = `retry_count` + 1, `retry_at` = DATE_ADD(NOW(), INTERVAL ? \
+                 SECOND), `locked_by` = NULL, `locked_until` = NULL \
+                 WHERE `id` = ? AND `locked_by` = ?
+                ",
+                delay,
+                self.id,
+                self.locked_by
+            )
+            .execute(pool)
+            .await
+            .expect(&format!("Could not schedule retry of Reminder {}", self.id));
+        }
+    }
+
+    /// Tell whoever set a reminder that it has been removed automatically. Goes to the guild's log
+    /// channel if one is configured, otherwise to the creator's DMs.
+    async fn notify_removal(&self, http: &Http, reason: &str) {
+        let location = if self.guild_id.is_some() {
+            format!("in <#{}>", self.target_channel())
+        } else {
+            "in a direct message".to_string()
+        };
+
+        let mut notice = format!("A reminder {} has been removed because {}.", location, reason);
+        if !self.content.is_empty() {
+            notice.push_str(&format!(
+                "\n> {}",
+                self.content.chars().take(200).collect::<String>().replace('\n', "\n> ")
+            ));
+        }
+
+        let result = if let Some(log_channel) = self.guild_log_channel {
+            ChannelId(log_channel)
+                .send_message(http, |m| {
+                    m.content(match self.set_by {
+                        Some(set_by) => format!("<@{}> {}", set_by, notice),
+                        None => notice,
+                    })
+                    .allowed_mentions(|a| a.users(self.set_by.map(UserId)))
+                })
+                .await
+        } else if let Some(set_by) = self.set_by {
+            match UserId(set_by).create_dm_channel(http).await {
+                Ok(dm) => dm.say(http, notice).await,
+                Err(e) => Err(e),
+            }
+        } else {
+            return;
+        };
+
+        if let Err(e) = result {
+            warn!("Could not notify removal of Reminder {}: {:?}", self.id, e);
+        }
+    }
+
+    /// Delete a reminder that has no occurrences left. Escalations and deletions of the messages it
+    /// has sent are detached from it first, as those messages still need marking done or deleting.
+    async fn delete_finished(&self, pool: impl Executor<'_, Database = Database> + Copy) {
+        if let Err(e) = sqlx::query!(
+            "UPDATE pending_escalations SET `reminder_id` = NULL WHERE `reminder_id` = ?",
+            self.id
+        )
+        .execute(pool)
+        .await
+        {
+            warn!("Could not detach escalations of Reminder {}: {:?}", self.id, e);
+        }
+
+        if let Err(e) = sqlx::query!(
+            "UPDATE pending_deletions SET `reminder_id` = NULL WHERE `reminder_id` = ?",
+            self.id
+        )
+        .execute(pool)
+        .await
+        {
+            warn!("Could not detach deletions of Reminder {}: {:?}", self.id, e);
+        }
+
+        self.force_delete(pool).await;
+    }
+
+    /// Delete a reminder that can no longer be sent. Its pending escalations and deletions are
+    /// removed with it.
+    async fn force_delete(&self, pool: impl Executor<'_, Database = Database> + Copy) {
+        sqlx::query!("DELETE FROM reminders WHERE `id` = ?", self.id)
+            .execute(pool)
+            .await
+            .expect(&format!("Could not delete Reminder {}", self.id));
+    }
+
+    async fn pin_message(&self, message: &Message, http: impl AsRef<Http>) {
+        let _ = http.as_ref().pin_message(message.channel_id.0, message.id.0, None).await;
+    }
+
+    /// Restrict who the message may ping. Only called for reminders with allowed mentions, as
+    /// the others ping anything their content mentions.
+    fn allowed_mentions<'a>(
+        &self,
+        a: &'a mut CreateAllowedMentions,
+    ) -> &'a mut CreateAllowedMentions {
+        if let Some(Json(allowed)) = &self.allowed_mentions {
+            a.empty_parse();
+
+            for value in &allowed.parse {
+                a.parse(value.clone());
+            }
+
+            a.users(allowed.users.iter().filter_map(|id| id.parse().ok()).map(UserId))
+                .roles(allowed.roles.iter().filter_map(|id| id.parse().ok()).map(RoleId));
+        }
+
+        a
+    }
+
+    /// Buttons to attach to this occurrence's message, if it has any.
+    fn components(&self) -> Option<CreateComponents> {
+        if !self.ack_button && !self.snooze_buttons {
+            return None;
+        }
+
+        let mut components = CreateComponents::default();
+
+        components.create_action_row(|row| {
+            if self.ack_button {
+                row.create_button(|b| {
+                    b.label("Mark done").style(ButtonStyle::Success).custom_id(
+                        ComponentDataModel::AcknowledgeReminder(AcknowledgeReminder {
+                            reminder_id: self.id,
+                            occurrence: self.occurrences + 1,
+                        })
+                        .to_custom_id(),
+                    )
+                });
+            }
+
+            if self.snooze_buttons {
+                for (label, minutes) in SNOOZE_TIMES {
+                    row.create_button(|b| {
+                        b.label(label).style(ButtonStyle::Secondary).custom_id(
+                            ComponentDataModel::SnoozeReminder(SnoozeReminder { minutes })
+                                .to_custom_id(),
+                        )
+                    });
+                }
+            }
+
+            row
+        });
+
+        Some(components)
+    }
+
+    /// Open a thread on a message that was just sent, for reminders that start a new thread on
+    /// each occurrence. Forum posts are opened as they are sent instead.
+    async fn open_thread(&self, message: &Message, http: impl AsRef<Http>) {
+        if let Some(title) = &self.thread_title {
+            let name = title.chars().take(MAX_THREAD_TITLE_LENGTH).collect::<String>();
+
+            if let Err(e) =
+                message.channel_id.create_public_thread(http, message.id, |t| t.name(name)).await
+            {
+                warn!("Could not open thread for Reminder {}: {:?}", self.id, e);
+            }
+        }
+    }
+
+    /// Remember the message sent for this occurrence, so the next one can replace it.
+    async fn set_last_message(
+        &self,
+        pool: impl Executor<'_, Database = Database> + Copy,
+        message_id: MessageId,
+    ) {
+        if let Err(e) = sqlx::query!(
+            "UPDATE reminders SET `last_message_id` = ? WHERE `id` = ?",
+            message_id.0,
+            self.id
+        )
+        .execute(pool)
+        .await
+        {
+            warn!("Could not store last message of Reminder {}: {:?}", self.id, e);
+        }
+    }
+
+    /// The variant of the content pool to send for this occurrence, if the reminder has a pool.
+    /// Variants are taken in turn from the stored cursor, or at random.
+    fn pool_variant(&self) -> Option<&str> {
+        let variants = &self.content_pool.as_ref()?.0;
+
+        if variants.is_empty() {
+            return None;
+        }
+
+        let index = if self.content_pool_mode == "random" {
+            rand::thread_rng().gen_range(0..variants.len())
+        } else {
+            self.content_pool_cursor as usize % variants.len()
+        };
+
+        Some(&variants[index])
+    }
+
+    /// Move the content pool on to the next variant once one has been sent.
+    async fn advance_pool_cursor(&self, pool: impl Executor<'_, Database = Database> + Copy) {
+        let len = self.content_pool.as_ref().map_or(0, |variants| variants.0.len()) as u32;
+
+        if self.content_pool_mode == "random" || len == 0 {
+            return;
+        }
+
+        if let Err(e) = sqlx::query!(
+            "UPDATE reminders SET `content_pool_cursor` = ? WHERE `id` = ?",
+            (self.content_pool_cursor + 1) % len,
+            self.id
+        )
+        .execute(pool)
+        .await
+        {
+            warn!("Could not advance content pool of Reminder {}: {:?}", self.id, e);
+        }
+    }
+
+    /// Queue the message sent for this occurrence to be deleted once `delete_after` has passed.
+    async fn schedule_deletion(
+        &self,
+        pool: impl Executor<'_, Database = Database> + Copy,
+        method: DeliveryMethod,
+        message: &Message,
+    ) {
+        if let Some(delete_after) = self.delete_after {
+            self.queue_deletion(pool, method, message.channel_id, message.id, delete_after).await;
+        }
+    }
+
+    /// Queue one of the reminder's messages to be deleted in `delay` seconds. `channel` is where
+    /// the message is, which differs from the reminder's channel for messages in a thread.
+    async fn queue_deletion(
+        &self,
+        pool: impl Executor<'_, Database = Database> + Copy,
+        method: DeliveryMethod,
+        channel: ChannelId,
+        message_id: MessageId,
+        delay: u32,
+    ) {
+        if let Err(e) = sqlx::query!(
+            "
+INSERT INTO pending_deletions (reminder_id, channel, thread_id, message_id, method, delete_at)
+VALUES (?, ?, ?, ?, ?, DATE_ADD(NOW(), INTERVAL ? SECOND))
+            ",
+            self.id,
+            self.channel_id,
+            // set when posting in a thread, or when a forum post was opened for the message
+            Some(channel.0).filter(|id| *id != self.channel_id),
+            message_id.0,
+            method.as_str(),
+            delay
+        )
+        .execute(pool)
+        .await
+        {
+            warn!("Could not schedule deletion for Reminder {}: {:?}", self.id, e);
+        }
+    }
+
+    /// Queue an escalation for this occurrence's message, to be sent if nobody marks it done
+    /// within `escalate_after`. Escalation relies on the "Mark done" button, so it is only queued
+    /// for reminders that have one.
+    async fn schedule_escalation(
+        &self,
+        pool: impl Executor<'_, Database = Database> + Copy,
+        message: &Message,
+    ) {
+        if let (Some(escalate_after), true) = (self.escalate_after, self.ack_button) {
+            if self.escalate_mentions.is_none() && self.escalate_user.is_none() {
+                return;
+            }
+
+            if let Err(e) = sqlx::query!(
+                "
+INSERT INTO pending_escalations
+    (reminder_id, guild_id, channel, message_id, occurrence, mentions, backup_user, escalate_after,
+    remaining, escalate_at)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, DATE_ADD(NOW(), INTERVAL ? SECOND))
+                ",
+                self.id,
+                self.guild_id,
+                message.channel_id.0,
+                message.id.0,
+                self.occurrences + 1,
+                self.escalate_mentions,
+                self.escalate_user,
+                escalate_after,
+                self.escalate_limit.max(1),
+                escalate_after
+            )
+            .execute(pool)
+            .await
+            {
+                warn!("Could not schedule escalation for Reminder {}: {:?}", self.id, e);
+            }
+        }
+    }
+
+    /// Replace the message sent for the previous occurrence, if the reminder is set to. In `edit`
+    /// mode the old message is edited to the new content and its ID returned; attachments can't be
+    /// changed this way, so the old ones are kept. In `repost` mode the old message is unpinned and
+    /// deleted so a new one can be sent in its place. Messages that have since been deleted are
+    /// ignored, and a new message is sent instead. If deleting the old message fails otherwise, it
+    /// is queued to be deleted again rather than forgotten.
+    async fn replace_previous(
+        &self,
+        pool: impl Executor<'_, Database = Database> + Copy,
+        cache_http: impl CacheHttp,
+        webhook: Option<&Webhook>,
+        embeds: &[CreateEmbed],
+    ) -> Option<Message> {
+        let message_id = MessageId(self.last_message_id?);
+
+        match self.replace_mode.as_deref() {
+            Some("edit") => {
+                let result = match webhook {
+                    Some(webhook) => {
+                        let webhook = match self.thread_id {
+                            Some(thread_id) => with_query(
+                                webhook,
+                                &format!("/messages/{}?thread_id={}", message_id, thread_id),
+                            ),
+                            None => webhook.clone(),
+                        };
+
+                        webhook
+                            .edit_message(cache_http.http(), message_id, |m| {
+                                m.content(&self.content).embeds(
+                                    embeds
+                                        .iter()
+                                        .map(|embed| {
+                                            SerenityEmbed::fake(|c| {
+                                                *c = embed.clone();
+                                                c
+                                            })
+                                        })
+                                        .collect(),
+                                );
+
+                                if let Some(components) = self.components() {
+                                    m.components(|c| {
+                                        *c = components;
+                                        c
+                                    });
+                                }
+
+                                m
+                            })
+                            .await
+                    }
+
+                    None => {
+                        self.target_channel()
+                            .edit_message(cache_http.http(), message_id, |m| {
+                                m.content(&self.content).set_embeds(embeds.to_vec());
+
+                                if let Some(components) = self.components() {
+                                    m.set_components(components);
+                                }
+
+                                m
+                            })
+                            .await
+                    }
+                };
+
+                match result {
+                    Ok(message) => Some(message),
+
+                    Err(e) => {
+                        info!("Could not edit last message of Reminder {}: {:?}", self.id, e);
+
+                        None
+                    }
+                }
+            }
+
+            Some("repost") => {
+                if self.pin {
+                    let _ = cache_http
+                        .http()
+                        .unpin_message(self.target_channel().0, message_id.0, None)
+                        .await;
+                }
+
+                let result = match webhook {
+                    Some(webhook) => {
+                        let webhook = match self.thread_id {
+                            Some(thread_id) => with_query(
+                                webhook,
+                                &format!("/messages/{}?thread_id={}", message_id, thread_id),
+                            ),
+                            None => webhook.clone(),
+                        };
+
+                        webhook.delete_message(cache_http.http(), message_id).await
+                    }
+                    None => {
+                        cache_http
+                            .http()
+                            .delete_message(self.target_channel().0, message_id.0)
+                            .await
+                    }
+                };
+
+                match result {
+                    Err(Error::Http(e)) if e.status_code() == Some(StatusCode::NOT_FOUND) => {}
+
+                    Err(e) => {
+                        warn!("Could not delete last message of Reminder {}: {:?}", self.id, e);
+
+                        let method = if webhook.is_some() {
+                            DeliveryMethod::Webhook
+                        } else {
+                            DeliveryMethod::Channel
+                        };
+
+                        self.queue_deletion(pool, method, self.target_channel(), message_id, 0)
+                            .await;
+                    }
+
+                    Ok(()) => {}
+                }
+
+                None
+            }
+
+            _ => None,
+        }
+    }
+
+    /// Whether an occurrence falls on one of the reminder's excluded dates, in its own timezone.
+    fn is_excluded(&self, time: &DateTime<Tz>) -> bool {
+        self.exclude_dates.as_ref().map_or(false, |dates| {
+            let date = time.format("%Y-%m-%d").to_string();
+
+            dates.iter().any(|excluded| *excluded == date)
+        })
+    }
+
+    /// Why this occurrence shouldn't be sent, if it shouldn't be.
+    fn skip_reason(&self) -> Option<&'static str> {
+        if !self.is_active() {
+            Some("Skipped: reminder disabled or channel paused")
+        } else if self.is_repeating() && self.skip_next {
+            Some("Skipped: occurrence skipped")
+        } else if self.is_repeating()
+            && self.is_excluded(
+                &self.utc_time.with_timezone(&self.timezone.parse().unwrap_or(Tz::UTC)),
+            )
+        {
+            Some("Skipped: excluded date")
+        } else {
+            None
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.enabled
+            && !(self.channel_paused
+                && self
+                    .channel_paused_until
+                    .map_or(true, |inner| inner >= Utc::now().naive_local()))
+    }
+
+    async fn unpause_channel(&self, pool: impl Executor<'_, Database = Database> + Copy) {
+        let _ = sqlx::query!(
+            "UPDATE `channels` SET paused = 0, paused_until = NULL WHERE `channel` = ?",
+            self.channel_id
+        )
+        .execute(pool)
+        .await;
+    }
+
+    async fn deliver(
+        &self,
+        pool: impl Executor<'_, Database = Database> + Copy,
+        cache_http: impl CacheHttp,
+        embeds: Vec<CreateEmbed>,
+        attachments: Vec<Attachment>,
+    ) -> (DeliveryMethod, Result<Option<Message>>) {
+        async fn send_to_channel(
+            cache_http: impl CacheHttp,
+            reminder: &Reminder,
+            embeds: Vec<CreateEmbed>,
+            attachments: &[Attachment],
+        ) -> Result<Option<Message>> {
+            let channel = reminder.target_channel().to_channel(&cache_http).await;
+
+            match channel {
+                Ok(Channel::Guild(channel)) if channel.kind == ChannelType::Forum => {
+                    Err(Error::Other("Forum posts can only be sent through a webhook"))
+                }
+                Ok(Channel::Guild(channel)) => {
+                    match channel
+                        .send_message(&cache_http, |m| {
+                            m.content(&reminder.content).tts(reminder.tts);
+
+                            if reminder.allowed_mentions.is_some() {
+                                m.allowed_mentions(|a| reminder.allowed_mentions(a));
+                            }
+
+                            for attachment in attachments {
+                                m.add_file((attachment.data.as_slice(), attachment.name.as_str()));
+                            }
+
+                            if !embeds.is_empty() {
+                                m.set_embeds(embeds);
+                            }
+
+                            if let Some(components) = reminder.components() {
+                                m.set_components(components);
+                            }
+
+                            m
+                        })
+                        .await
+                    {
+                        Ok(m) => {
+                            if reminder.pin {
+                                reminder.pin_message(&m, cache_http.http()).await;
+                            }
+
+                            Ok(Some(m))
+                        }
+                        Err(e) => Err(e),
                     }
+                }
+                Ok(Channel::Private(channel)) => {
+                    match channel
+                        .send_message(&cache_http.http(), |m| {
+                            m.content(&reminder.content).tts(reminder.tts);
 
-                    if let (Some(attachment), Some(name)) =
-                        (&reminder.attachment, &reminder.attachment_name)
+                            if reminder.allowed_mentions.is_some() {
+                                m.allowed_mentions(|a| reminder.allowed_mentions(a));
+                            }
+
+                            for attachment in attachments {
+                                m.add_file((attachment.data.as_slice(), attachment.name.as_str()));
+                            }
+
+                            if !embeds.is_empty() {
+                                m.set_embeds(embeds);
+                            }
+
+                            if let Some(components) = reminder.components() {
+                                m.set_components(components);
+                            }
+
+                            m
+                        })
+                        .await
                     {
-                        w.add_file((attachment as &[u8], name.as_str()));
+                        Ok(m) => {
+                            if reminder.pin {
+                                reminder.pin_message(&m, cache_http.http()).await;
+                            }
+
+                            Ok(Some(m))
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+                Err(e) => Err(e),
+                _ => Err(Error::Other("Channel not of valid type")),
+            }
+        }
+
+        async fn send_to_webhook(
+            cache_http: impl CacheHttp,
+            reminder: &Reminder,
+            webhook: Webhook,
+            embeds: Vec<CreateEmbed>,
+            attachments: &[Attachment],
+            forum: bool,
+        ) -> Result<Option<Message>> {
+            // always wait for the message so its ID can be recorded
+            let webhook = match reminder.thread_id {
+                Some(thread_id) => {
+                    with_query(&webhook, &format!("?wait=true&thread_id={}", thread_id))
+                }
+                None => webhook,
+            };
+
+            match webhook
+                .execute(&cache_http.http(), true, |w| {
+                    w.content(&reminder.content).tts(reminder.tts);
+
+                    if reminder.allowed_mentions.is_some() {
+                        w.allowed_mentions(|a| reminder.allowed_mentions(a));
                     }
 
-                    if let Some(embed) = embed {
-                        w.embeds(vec![SerenityEmbed::fake(|c| {
-                            *c = embed;
-                            c
-                        })]);
+                    if let Some(username) = &reminder.username {
+                        w.username(username);
+                    }
+
+                    if let Some(avatar) = &reminder.avatar {
+                        w.avatar_url(avatar);
+                    }
+
+                    // webhooks in forum channels open a new post with each message
+                    if forum {
+                        if let Some(title) = &reminder.thread_title {
+                            let name =
+                                title.chars().take(MAX_THREAD_TITLE_LENGTH).collect::<String>();
+
+                            w.0.insert("thread_name", Value::from(name.as_str()));
+                        }
+                    }
+
+                    for attachment in attachments {
+                        w.add_file((attachment.data.as_slice(), attachment.name.as_str()));
+                    }
+
+                    if !embeds.is_empty() {
+                        w.embeds(
+                            embeds
+                                .into_iter()
+                                .map(|embed| {
+                                    SerenityEmbed::fake(|c| {
+                                        *c = embed;
+                                        c
+                                    })
+                                })
+                                .collect(),
+                        );
+                    }
+
+                    if let Some(components) = reminder.components() {
+                        w.set_components(components);
                     }
 
                     w
@@ -528,75 +1540,493 @@ WHERE
             {
                 Ok(m) => {
                     if reminder.pin {
-                        if let Some(message) = m {
-                            reminder.pin_message(message.id, cache_http.http()).await;
+                        if let Some(message) = &m {
+                            reminder.pin_message(message, cache_http.http()).await;
                         }
                     }
 
-                    Ok(())
+                    Ok(m)
                 }
                 Err(e) => Err(e),
             }
         }
 
-        if self.enabled
-            && !(self.channel_paused
-                && self
-                    .channel_paused_until
-                    .map_or(true, |inner| inner >= Utc::now().naive_local()))
+        // the channel's webhook also posts into its threads, keeping the reminder's name and avatar
+        let webhook = if let (Some(webhook_id), Some(webhook_token)) =
+            (self.webhook_id, &self.webhook_token)
         {
-            let _ = sqlx::query!(
-                "UPDATE `channels` SET paused = 0, paused_until = NULL WHERE `channel` = ?",
-                self.channel_id
-            )
-            .execute(pool)
-            .await;
+            let webhook_res =
+                cache_http.http().get_webhook_with_token(webhook_id, webhook_token).await;
 
-            let embed = Embed::from_id(pool, self.id).await.map(|e| e.into());
+            if let Ok(webhook) = webhook_res {
+                Some(webhook)
+            } else {
+                warn!("Webhook vanished: {:?}", webhook_res);
 
-            let result = if let (Some(webhook_id), Some(webhook_token)) =
-                (self.webhook_id, &self.webhook_token)
-            {
-                let webhook_res =
-                    cache_http.http().get_webhook_with_token(webhook_id, webhook_token).await;
+                self.reset_webhook(pool).await;
+                None
+            }
+        } else {
+            None
+        };
 
-                if let Ok(webhook) = webhook_res {
-                    send_to_webhook(cache_http, &self, webhook, embed).await
-                } else {
-                    warn!("Webhook vanished: {:?}", webhook_res);
+        let method =
+            if webhook.is_some() { DeliveryMethod::Webhook } else { DeliveryMethod::Channel };
 
-                    self.reset_webhook(pool).await;
-                    send_to_channel(cache_http, &self, embed).await
-                }
-            } else {
-                send_to_channel(cache_http, &self, embed).await
-            };
+        if let Some(message) =
+            self.replace_previous(pool, &cache_http, webhook.as_ref(), &embeds).await
+        {
+            return (method, Ok(Some(message)));
+        }
 
-            if let Err(e) = result {
-                error!("Error sending reminder {}: {:?}", self.id, e);
+        let forum = self.thread_id.is_none()
+            && self.thread_title.is_some()
+            && matches!(
+                ChannelId(self.channel_id).to_channel(&cache_http).await,
+                Ok(Channel::Guild(channel)) if channel.kind == ChannelType::Forum
+            );
 
-                if let Error::Http(error) = e {
-                    if error.status_code() == Some(StatusCode::NOT_FOUND) {
-                        warn!("Seeing channel is deleted. Removing reminder");
+        let result = match webhook {
+            Some(webhook) => {
+                send_to_webhook(&cache_http, &self, webhook, embeds, &attachments, forum).await
+            }
+            None => send_to_channel(&cache_http, &self, embeds, &attachments).await,
+        };
+
+        if let Ok(Some(message)) = &result {
+            if self.thread_id.is_none() && !forum {
+                self.open_thread(message, cache_http.http()).await;
+            }
+        }
+
+        (method, result)
+    }
+
+    async fn record_delivery(
+        &self,
+        pool: impl Executor<'_, Database = Database> + Copy,
+        method: Option<DeliveryMethod>,
+        result: std::result::Result<Option<(ChannelId, MessageId)>, String>,
+    ) {
+        let (sent_to, error) = match result {
+            Ok(sent_to) => (sent_to, None),
+            Err(error) => (None, Some(error.chars().take(1024).collect::<String>())),
+        };
+
+        if let Err(e) = sqlx::query!(
+            "
+INSERT INTO reminder_deliveries
+    (reminder_id, reminder_uid, channel, thread_id, message_id, method, error, content)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+            self.id,
+            self.uid,
+            self.channel_id,
+            sent_to.map(|(channel, _)| channel.0).filter(|channel| *channel != self.channel_id),
+            sent_to.map(|(_, message)| message.0),
+            method.map(|m| m.as_str()),
+            error,
+            self.content.chars().take(100).collect::<String>()
+        )
+        .execute(pool)
+        .await
+        {
+            warn!("Could not record delivery of Reminder {}: {:?}", self.id, e);
+        }
+    }
+
+    /// Update or remove the reminder depending on the outcome of sending it.
+    async fn finish(
+        &self,
+        pool: impl Executor<'_, Database = Database> + Copy,
+        http: &Http,
+        result: &Result<Option<Message>>,
+    ) {
+        if let Err(e) = result {
+            error!("Error sending reminder {}: {:?}", self.id, e);
+
+            if let Error::Http(error) = e {
+                if error.status_code() == Some(StatusCode::NOT_FOUND) {
+                    warn!("Seeing channel is deleted. Removing reminder");
+                    self.notify_removal(
+                        http,
+                        "its channel was deleted or can no longer be accessed",
+                    )
+                    .await;
+                    self.force_delete(pool).await;
+                } else if error.status_code().map_or(false, |code| {
+                    code.is_server_error() || code == StatusCode::TOO_MANY_REQUESTS
+                }) {
+                    self.retry(pool, http).await;
+                } else if let HttpError::UnsuccessfulRequest(error) = error.as_ref() {
+                    if error.error.code == 50007 {
+                        warn!("User cannot receive DMs");
+                        self.notify_removal(http, "the recipient doesn't accept direct messages")
+                            .await;
                         self.force_delete(pool).await;
-                    } else if let HttpError::UnsuccessfulRequest(error) = *error {
-                        if error.error.code == 50007 {
-                            warn!("User cannot receive DMs");
-                            self.force_delete(pool).await;
-                        } else {
-                            self.refresh(pool).await;
-                        }
+                    } else {
+                        self.refresh(pool, http, false).await;
                     }
                 } else {
-                    self.refresh(pool).await;
+                    // the request didn't complete, e.g. it timed out or the connection dropped
+                    self.retry(pool, http).await;
                 }
             } else {
-                self.refresh(pool).await;
+                self.refresh(pool, http, false).await;
             }
         } else {
-            info!("Reminder {} is paused", self.id);
+            self.refresh(pool, http, true).await;
+        }
+    }
 
-            self.refresh(pool).await;
+    pub async fn send(
+        &self,
+        pool: impl Executor<'_, Database = Database> + Copy,
+        cache_http: impl CacheHttp,
+    ) {
+        if !self.renew_lease(pool).await {
+            return;
         }
+
+        if let Some(reason) = self.skip_reason() {
+            info!("Reminder {} is not being sent. {}", self.id, reason);
+
+            self.record_delivery(pool, None, Err(reason.to_string())).await;
+            self.refresh(pool, cache_http.http(), false).await;
+        } else if self.missed_policy == "skip" && self.is_late() {
+            info!("Reminder {} was missed. Skipping", self.id);
+
+            self.record_delivery(pool, None, Err("Skipped: missed while offline".to_string()))
+                .await;
+            self.refresh(pool, cache_http.http(), false).await;
+        } else {
+            self.unpause_channel(pool).await;
+
+            // reminders with a content pool send one of its variants as their content
+            let pooled = self
+                .pool_variant()
+                .map(|variant| Self { content: self.render(variant), ..self.clone() });
+            let reminder = pooled.as_ref().unwrap_or(self);
+
+            let embeds: Vec<CreateEmbed> = Embed::from_id(pool, self)
+                .await
+                .into_iter()
+                .chain(Embed::extras(pool, self).await)
+                .take(MAX_EMBEDS)
+                .map(|e| e.into())
+                .collect();
+            let attachments = Attachment::for_reminder(pool, self).await;
+
+            let (method, result) = reminder.deliver(pool, &cache_http, embeds, attachments).await;
+
+            if pooled.is_some() && result.is_ok() {
+                self.advance_pool_cursor(pool).await;
+            }
+
+            if let Ok(Some(message)) = &result {
+                if self.replace_mode.is_some() {
+                    self.set_last_message(pool, message.id).await;
+                }
+
+                self.schedule_deletion(pool, method, message).await;
+                self.schedule_escalation(pool, message).await;
+            }
+
+            reminder
+                .record_delivery(
+                    pool,
+                    Some(method),
+                    result
+                        .as_ref()
+                        .map(|m| m.as_ref().map(|m| (m.channel_id, m.id)))
+                        .map_err(|e| e.to_string()),
+                )
+                .await;
+            self.finish(pool, cache_http.http(), &result).await;
+        }
+    }
+
+    /// Send a group of reminders produced by `group_digest` as a single message. Content is joined
+    /// line by line, and the outcome of sending is applied to every reminder in the group.
+    pub async fn send_digest(
+        group: Vec<Self>,
+        pool: impl Executor<'_, Database = Database> + Copy,
+        cache_http: impl CacheHttp,
+    ) {
+        let (active, skipped): (Vec<Self>, Vec<Self>) =
+            group.into_iter().partition(|reminder| reminder.skip_reason().is_none());
+
+        for reminder in skipped {
+            // `send` records why the reminder was skipped
+            reminder.send(pool, &cache_http).await;
+        }
+
+        let active = keep_leased(active, pool).await;
+
+        match active.len() {
+            0 => {}
+            // `send` renews the lease again, which is harmless
+            1 => active[0].send(pool, cache_http).await,
+            _ => {
+                active[0].unpause_channel(pool).await;
+
+                let mut embeds = vec![];
+                for reminder in &active {
+                    if let Some(embed) = Embed::from_id(pool, reminder).await {
+                        embeds.push(embed.into());
+                    }
+                }
+
+                let mut digest = active[0].clone();
+                digest.content = active
+                    .iter()
+                    .map(|reminder| reminder.content.as_str())
+                    .filter(|content| !content.is_empty())
+                    .collect::<Vec<&str>>()
+                    .join("\n");
+                digest.tts = active.iter().any(|reminder| reminder.tts);
+                digest.pin = active.iter().any(|reminder| reminder.pin);
+
+                // reminders with attachments are never merged into a digest
+                let (method, result) = digest.deliver(pool, &cache_http, embeds, vec![]).await;
+
+                for reminder in &active {
+                    reminder
+                        .record_delivery(
+                            pool,
+                            Some(method),
+                            result
+                                .as_ref()
+                                .map(|m| m.as_ref().map(|m| (m.channel_id, m.id)))
+                                .map_err(|e| e.to_string()),
+                        )
+                        .await;
+                    reminder.finish(pool, cache_http.http(), &result).await;
+                }
+            }
+        }
+    }
+}
+
+/// A copy of `webhook` whose requests have `path_and_query` appended after the token, e.g. to set
+/// `thread_id`, which serenity can't pass to webhook endpoints. The rest of the path serenity puts
+/// after the token is moved into the URL fragment, so isn't sent, and has to be part of
+/// `path_and_query` instead.
+fn with_query(webhook: &Webhook, path_and_query: &str) -> Webhook {
+    let mut webhook = webhook.clone();
+    webhook.token = webhook.token.map(|token| token_with_query(&token, path_and_query));
+
+    webhook
+}
+
+/// A webhook token with `path_and_query` appended, as for `with_query`, for calling `Http`
+/// directly.
+pub fn token_with_query(token: &str, path_and_query: &str) -> String {
+    format!("{}{}#", token, path_and_query)
+}
+
+/// Remove delivery records older than `days`, a batch at a time so a large backlog doesn't hold
+/// up sending. Retention of 0 keeps records forever.
+pub async fn prune_deliveries(pool: impl Executor<'_, Database = Database>, days: u32) {
+    if days == 0 {
+        return;
+    }
+
+    if let Err(e) = sqlx::query!(
+        "DELETE FROM reminder_deliveries WHERE sent_at < DATE_SUB(NOW(), INTERVAL ? DAY) \
+         LIMIT 1000",
+        days
+    )
+    .execute(pool)
+    .await
+    {
+        warn!("Could not prune reminder deliveries: {:?}", e);
+    }
+}
+
+/// Delay in seconds before retrying something that has already failed `attempts` times
+pub fn retry_delay(attempts: u32) -> u64 {
+    RETRY_BASE_DELAY * 2u64.pow(attempts)
+}
+
+/// Whether a failed request may succeed later: Discord had a server error or ratelimited it, or the
+/// request didn't complete
+pub fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::Http(error) => match error.as_ref() {
+            HttpError::UnsuccessfulRequest(response) => {
+                response.status_code.is_server_error()
+                    || response.status_code == StatusCode::TOO_MANY_REQUESTS
+            }
+            _ => true,
+        },
+        _ => false,
+    }
+}
+
+/// Renew the leases on a batch of reminders about to be sent together, dropping those claimed by
+/// another instance.
+async fn keep_leased(
+    reminders: Vec<Reminder>,
+    pool: impl Executor<'_, Database = Database> + Copy,
+) -> Vec<Reminder> {
+    let mut leased = vec![];
+
+    for reminder in reminders {
+        if reminder.renew_lease(pool).await {
+            leased.push(reminder);
+        }
+    }
+
+    leased
+}
+
+/// Take the reminders in a channel's queue that were missed and should be summarised rather than
+/// sent, leaving the rest of the queue in order.
+pub fn take_missed(queue: Vec<Reminder>) -> (Vec<Reminder>, Vec<Reminder>) {
+    queue.into_iter().partition(|reminder| {
+        reminder.skip_reason().is_none() && reminder.missed_policy == "digest" && reminder.is_late()
+    })
+}
+
+/// Post a summary of missed reminders in place of the reminders themselves.
+pub async fn send_missed(
+    missed: Vec<Reminder>,
+    pool: impl Executor<'_, Database = Database> + Copy,
+    cache_http: impl CacheHttp,
+) {
+    const HEADER: &str = "**Missed while offline:**";
+
+    let missed = keep_leased(missed, pool).await;
+
+    let mut chunks: Vec<(String, Vec<&Reminder>)> = vec![];
+
+    for reminder in &missed {
+        let line = format!(
+            "\n<t:{}:f> {}",
+            reminder.utc_time.timestamp(),
+            reminder.content.chars().take(200).collect::<String>().replace('\n', " ")
+        );
+
+        match chunks.last_mut() {
+            Some((text, members))
+                if text.chars().count() + line.chars().count() <= MAX_CONTENT_LENGTH =>
+            {
+                text.push_str(&line);
+                members.push(reminder);
+            }
+
+            _ => chunks.push((format!("{}{}", HEADER, line), vec![reminder])),
+        }
+    }
+
+    for (text, members) in chunks {
+        let mut notice = members[0].clone();
+        notice.content = text;
+        notice.tts = false;
+        notice.pin = false;
+        notice.attachment = None;
+        notice.attachment_name = None;
+        notice.replace_mode = None;
+        notice.delete_after = None;
+        notice.thread_title = None;
+        notice.ack_button = false;
+        notice.snooze_buttons = false;
+        notice.escalate_after = None;
+        // the notice quotes the missed reminders, so shouldn't ping anyone
+        notice.allowed_mentions = Some(Json(AllowedMentions::default()));
+        notice.content_pool = None;
+
+        let (method, result) = notice.deliver(pool, &cache_http, vec![], vec![]).await;
+
+        for reminder in members {
+            reminder
+                .record_delivery(
+                    pool,
+                    Some(method),
+                    result
+                        .as_ref()
+                        .map(|m| m.as_ref().map(|m| (m.channel_id, m.id)))
+                        .map_err(|e| e.to_string()),
+                )
+                .await;
+            reminder.finish(pool, cache_http.http(), &result).await;
+        }
+    }
+}
+
+/// Split a channel's queue into runs of reminders due in the same minute that can be merged into
+/// one message. Reminders with attachments, several embeds, buttons or a content pool, that replace
+/// or delete their messages, or that open a thread are always sent on their own. Reminders are only
+/// merged with others in the same thread, under the same username and avatar, and with the same
+/// allowed mentions, which the merged message is sent with. Since reminders set by users without
+/// the Mention Everyone permission are restricted to users and roles, they aren't merged with
+/// unrestricted ones.
+pub fn group_digest(queue: Vec<Reminder>) -> Vec<Vec<Reminder>> {
+    let mut groups: Vec<Vec<Reminder>> = vec![];
+
+    for reminder in queue {
+        let fits = groups.last().map_or(false, |group| {
+            let first = &group[0];
+            let length = group.iter().map(|r| r.content.chars().count() + 1).sum::<usize>()
+                + reminder.content.chars().count();
+
+            first.attachment.is_none()
+                && reminder.attachment.is_none()
+                && first.extra_attachments == 0
+                && reminder.extra_attachments == 0
+                && first.extra_embeds == 0
+                && reminder.extra_embeds == 0
+                && first.replace_mode.is_none()
+                && reminder.replace_mode.is_none()
+                && first.delete_after.is_none()
+                && reminder.delete_after.is_none()
+                && first.thread_title.is_none()
+                && reminder.thread_title.is_none()
+                && !first.ack_button
+                && !reminder.ack_button
+                && !first.snooze_buttons
+                && !reminder.snooze_buttons
+                && first.allowed_mentions == reminder.allowed_mentions
+                && first.content_pool.is_none()
+                && reminder.content_pool.is_none()
+                && first.thread_id == reminder.thread_id
+                && first.username == reminder.username
+                && first.avatar == reminder.avatar
+                && first.utc_time.timestamp() / 60 == reminder.utc_time.timestamp() / 60
+                && group.len() < MAX_DIGEST_SIZE
+                && length <= MAX_CONTENT_LENGTH
+        });
+
+        if fits {
+            groups.last_mut().unwrap().push(reminder);
+        } else {
+            groups.push(vec![reminder]);
+        }
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use serenity::http::routing::Route;
+
+    use super::*;
+
+    #[test]
+    fn token_with_query_posts_into_thread() {
+        let token = token_with_query("token", "?wait=true&thread_id=2");
+        let url = Route::webhook_with_token_optioned(1, &token, true);
+
+        assert!(url.ends_with("/webhooks/1/token?wait=true&thread_id=2#?wait=true"));
+    }
+
+    #[test]
+    fn token_with_query_edits_and_deletes_in_thread() {
+        let token = token_with_query("token", "/messages/3?thread_id=2");
+        let url = Route::webhook_message(1, &token, 3);
+
+        assert!(url.ends_with("/webhooks/1/token/messages/3?thread_id=2#/messages/3"));
     }
 }
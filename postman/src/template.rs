@@ -0,0 +1,572 @@
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, NaiveDateTime, Utc,
+};
+use chrono_tz::Tz;
+use num_integer::Integer;
+
+/// Styles accepted by Discord's `<t:...>` timestamp markup.
+const TIMESTAMP_STYLES: [&str; 7] = ["t", "T", "d", "D", "f", "F", "R"];
+
+/// Values available to a template when a reminder is sent.
+pub struct TemplateContext {
+    pub channel: Option<u64>,
+    pub guild: Option<String>,
+    pub creator: Option<u64>,
+    /// The occurrence being sent, counting from 1
+    pub occurrence: u32,
+    pub max_occurrences: Option<u32>,
+    pub next: Option<DateTime<Utc>>,
+    pub now: DateTime<Utc>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Variable {
+    Channel,
+    Guild,
+    Creator,
+    Occurrence,
+    MaxOccurrences,
+    Next,
+    Now,
+}
+
+impl Variable {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "channel" => Some(Variable::Channel),
+            "guild" | "server" => Some(Variable::Guild),
+            "creator" => Some(Variable::Creator),
+            "occurrence" => Some(Variable::Occurrence),
+            "max_occurrences" => Some(Variable::MaxOccurrences),
+            "next" => Some(Variable::Next),
+            "now" => Some(Variable::Now),
+            _ => None,
+        }
+    }
+
+    fn text(&self, context: &TemplateContext) -> Option<String> {
+        match self {
+            Variable::Channel => context.channel.map(|id| format!("<#{}>", id)),
+            Variable::Guild => context.guild.clone(),
+            Variable::Creator => context.creator.map(|id| format!("<@{}>", id)),
+            Variable::Occurrence => Some(context.occurrence.to_string()),
+            Variable::MaxOccurrences => context.max_occurrences.map(|max| max.to_string()),
+            Variable::Next => context.next.map(|next| format!("<t:{}>", next.timestamp())),
+            Variable::Now => Some(format!("<t:{}>", context.now.timestamp())),
+        }
+    }
+
+    /// The variable as a number, for comparisons. Times compare as Unix timestamps.
+    fn number(&self, context: &TemplateContext) -> Option<i64> {
+        match self {
+            Variable::Occurrence => Some(context.occurrence as i64),
+            Variable::MaxOccurrences => context.max_occurrences.map(|max| max as i64),
+            Variable::Next => context.next.map(|next| next.timestamp()),
+            Variable::Now => Some(context.now.timestamp()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Operand {
+    Variable(Variable),
+    Number(i64),
+}
+
+impl Operand {
+    fn parse(operand: &str) -> Result<Self, String> {
+        match Variable::parse(operand) {
+            Some(variable) => Ok(Operand::Variable(variable)),
+            None => operand
+                .parse::<i64>()
+                .map(Operand::Number)
+                .map_err(|_| format!("`{}` is not a variable or a number", operand)),
+        }
+    }
+
+    fn number(&self, context: &TemplateContext) -> Option<i64> {
+        match self {
+            Operand::Variable(variable) => variable.number(context),
+            Operand::Number(number) => Some(*number),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn parse(operator: &str) -> Option<Self> {
+        match operator {
+            "==" => Some(Comparison::Equal),
+            "!=" => Some(Comparison::NotEqual),
+            "<" => Some(Comparison::Less),
+            "<=" => Some(Comparison::LessOrEqual),
+            ">" => Some(Comparison::Greater),
+            ">=" => Some(Comparison::GreaterOrEqual),
+            _ => None,
+        }
+    }
+
+    fn holds(&self, left: i64, right: i64) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+enum Condition {
+    /// The variable has a value, e.g. the reminder has a next occurrence
+    Set(Variable),
+    Compare(Operand, Comparison, Operand),
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    fn parse(condition: &str) -> Result<Self, String> {
+        let words = condition.split_whitespace().collect::<Vec<&str>>();
+
+        match words.as_slice() {
+            ["not", rest @ ..] if !rest.is_empty() => {
+                Ok(Condition::Not(Box::new(Condition::parse(&rest.join(" "))?)))
+            }
+
+            [name] => Variable::parse(name)
+                .map(Condition::Set)
+                .ok_or_else(|| format!("`{}` is not a variable", name)),
+
+            [left, operator, right] => match Comparison::parse(operator) {
+                Some(comparison) => Ok(Condition::Compare(
+                    Operand::parse(left)?,
+                    comparison,
+                    Operand::parse(right)?,
+                )),
+                None => Err(format!("`{}` is not a comparison", operator)),
+            },
+
+            _ => Err(format!("`{}` is not a condition", condition)),
+        }
+    }
+
+    fn holds(&self, context: &TemplateContext) -> bool {
+        match self {
+            Condition::Set(variable) => variable.text(context).is_some(),
+
+            // comparisons with a missing value never hold
+            Condition::Compare(left, comparison, right) => {
+                match (left.number(context), right.number(context)) {
+                    (Some(left), Some(right)) => comparison.holds(left, right),
+                    _ => false,
+                }
+            }
+
+            Condition::Not(condition) => !condition.holds(context),
+        }
+    }
+}
+
+/// A time given to `<<timestamp:...>>`: `now`, `next` or a Unix timestamp, optionally offset by
+/// a number of seconds, e.g. `now+300`.
+struct TimeValue {
+    base: Option<Variable>,
+    offset: i64,
+}
+
+impl TimeValue {
+    fn parse(value: &str) -> Result<Self, String> {
+        let (base, offset) = match value.find(|c| c == '+' || c == '-') {
+            Some(index) if index > 0 => value.split_at(index),
+            _ => (value, ""),
+        };
+
+        let offset = match offset {
+            "" => 0,
+            offset => offset
+                .trim_start_matches('+')
+                .parse::<i64>()
+                .map_err(|_| format!("`{}` is not an offset in seconds", offset))?,
+        };
+
+        match base {
+            "now" => Ok(TimeValue { base: Some(Variable::Now), offset }),
+            "next" => Ok(TimeValue { base: Some(Variable::Next), offset }),
+            timestamp => timestamp
+                .parse::<i64>()
+                .ok()
+                .and_then(|timestamp| timestamp.checked_add(offset))
+                .map(|timestamp| TimeValue { base: None, offset: timestamp })
+                .ok_or_else(|| format!("`{}` is not `now`, `next` or a Unix timestamp", timestamp)),
+        }
+    }
+
+    fn timestamp(&self, context: &TemplateContext) -> Option<i64> {
+        match self.base {
+            Some(variable) => {
+                variable.number(context).and_then(|time| time.checked_add(self.offset))
+            }
+            None => Some(self.offset),
+        }
+    }
+}
+
+enum Node {
+    Text(String),
+    Variable(Variable),
+    Timestamp(TimeValue, Option<String>),
+    TimeFrom(i64, String),
+    TimeNow(Tz, String),
+    If(Condition, Vec<Node>, Vec<Node>),
+}
+
+/// Tags that open, split or close a block, which `parse_nodes` hands back to its caller.
+enum Block {
+    If(Condition),
+    Else,
+    End,
+}
+
+enum Tag {
+    Node(Node),
+    Block(Block),
+}
+
+/// A parsed reminder template. Tags are written between `<<` and `>>`:
+///  - variables: `<<channel>>`, `<<guild>>`, `<<creator>>`, `<<occurrence>>`,
+///    `<<max_occurrences>>`, `<<next>>` and `<<now>>`
+///  - `<<timestamp:TIME:STYLE>>` for Discord timestamp markup, where `TIME` is `now`, `next` or a
+///    Unix timestamp, optionally offset like `now+300`, and `STYLE` is one of Discord's styles
+///  - `<<timefrom:TIMESTAMP:FORMAT>>` for the time to or from a Unix timestamp, formatted with
+///    `%d`, `%h`, `%m` and `%s`
+///  - `<<timenow:TIMEZONE:FORMAT>>` for the current time in a timezone, formatted by `strftime`
+///  - `<<if CONDITION>>...<<else>>...<<end>>`, where `CONDITION` is a variable, which holds if it
+///    has a value, or a comparison such as `occurrence == max_occurrences`, and may start with
+///    `not`
+///
+/// Text that only looks like a tag, such as `<< ` or `<<<`, and tags that aren't one of the above,
+/// such as `<<word>>` in content written before templates, are left as they are.
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+pub fn parse_template(template: &str) -> Result<Template, String> {
+    let mut rest = template;
+
+    let (nodes, block) = parse_nodes(&mut rest)?;
+
+    match block {
+        None => Ok(Template { nodes }),
+        Some(Block::Else) => Err("`<<else>>` without `<<if ...>>`".to_string()),
+        Some(Block::End) => Err("`<<end>>` without `<<if ...>>`".to_string()),
+        Some(Block::If(_)) => unreachable!(),
+    }
+}
+
+/// Parse nodes up to the end of the template or the next `<<else>>` or `<<end>>`, which is
+/// returned alongside them.
+fn parse_nodes(rest: &mut &str) -> Result<(Vec<Node>, Option<Block>), String> {
+    let mut nodes = vec![];
+    let mut text = String::new();
+
+    loop {
+        let (before, tag) = match next_tag(rest) {
+            Some(found) => found,
+            None => {
+                text.push_str(rest);
+                *rest = "";
+                break;
+            }
+        };
+
+        text.push_str(before);
+
+        let block = match parse_tag(tag)? {
+            Tag::Node(node) => {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(std::mem::take(&mut text)));
+                }
+                nodes.push(node);
+
+                continue;
+            }
+            Tag::Block(block) => block,
+        };
+
+        if !text.is_empty() {
+            nodes.push(Node::Text(std::mem::take(&mut text)));
+        }
+
+        match block {
+            Block::If(condition) => {
+                let (then, block) = parse_nodes(rest)?;
+
+                let otherwise = match block {
+                    Some(Block::End) => vec![],
+                    Some(Block::Else) => match parse_nodes(rest)? {
+                        (otherwise, Some(Block::End)) => otherwise,
+                        (_, Some(Block::Else)) => {
+                            return Err("`<<if ...>>` has more than one `<<else>>`".to_string());
+                        }
+                        _ => return Err("`<<if ...>>` is missing its `<<end>>`".to_string()),
+                    },
+                    _ => return Err("`<<if ...>>` is missing its `<<end>>`".to_string()),
+                };
+
+                nodes.push(Node::If(condition, then, otherwise));
+            }
+
+            block => return Ok((nodes, Some(block))),
+        }
+    }
+
+    if !text.is_empty() {
+        nodes.push(Node::Text(text));
+    }
+
+    Ok((nodes, None))
+}
+
+/// Find the next tag, returning the text before it and the tag's contents, and move `rest` past
+/// it. Only `<<` directly followed by a letter and closed by `>>` starts a tag.
+fn next_tag<'a>(rest: &mut &'a str) -> Option<(&'a str, &'a str)> {
+    let string: &'a str = *rest;
+    let mut from = 0;
+
+    while let Some(start) = string[from..].find("<<").map(|index| index + from) {
+        let body = &string[start + 2..];
+
+        if body.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            if let Some(end) = body.find(">>") {
+                *rest = &body[end + 2..];
+
+                return Some((&string[..start], &body[..end]));
+            }
+        }
+
+        from = start + 1;
+    }
+
+    None
+}
+
+fn parse_tag(tag: &str) -> Result<Tag, String> {
+    if let Some(condition) = tag.strip_prefix("if ") {
+        return Ok(Tag::Block(Block::If(Condition::parse(condition)?)));
+    }
+
+    let mut parts = tag.splitn(3, ':');
+    let name = parts.next().unwrap_or_default();
+
+    let node = match (name, parts.next(), parts.next()) {
+        ("else", None, None) => return Ok(Tag::Block(Block::Else)),
+        ("end", None, None) => return Ok(Tag::Block(Block::End)),
+
+        ("timestamp", Some(time), style) => {
+            if let Some(style) = style {
+                if !TIMESTAMP_STYLES.contains(&style) {
+                    return Err(format!(
+                        "`{}` is not a timestamp style. Use one of {}",
+                        style,
+                        TIMESTAMP_STYLES.join(", ")
+                    ));
+                }
+            }
+
+            Node::Timestamp(TimeValue::parse(time)?, style.map(|s| s.to_string()))
+        }
+
+        ("timefrom", Some(time), Some(format)) => match time.parse::<i64>() {
+            Ok(time) if NaiveDateTime::from_timestamp_opt(time, 0).is_some() => {
+                Node::TimeFrom(time, format.to_string())
+            }
+            _ => return Err(format!("`{}` is not a Unix timestamp", time)),
+        },
+
+        ("timenow", Some(timezone), Some(format)) => match timezone.parse::<Tz>() {
+            Ok(timezone) => {
+                if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                    return Err(format!("`{}` is not a valid time format", format));
+                }
+
+                Node::TimeNow(timezone, format.to_string())
+            }
+            Err(_) => return Err(format!("`{}` is not a timezone", timezone)),
+        },
+
+        ("timefrom", ..) | ("timenow", ..) => {
+            return Err(format!("`<<{}>>` needs a value and a format", tag));
+        }
+
+        (name, None, None) => match Variable::parse(name) {
+            Some(variable) => Node::Variable(variable),
+            None => Node::Text(format!("<<{}>>", tag)),
+        },
+
+        _ => Node::Text(format!("<<{}>>", tag)),
+    };
+
+    Ok(Tag::Node(node))
+}
+
+impl Template {
+    pub fn render(&self, context: &TemplateContext) -> String {
+        let mut output = String::new();
+
+        render_nodes(&self.nodes, context, &mut output);
+
+        output
+    }
+}
+
+fn render_nodes(nodes: &[Node], context: &TemplateContext, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+
+            Node::Variable(variable) => {
+                output.push_str(&variable.text(context).unwrap_or_default());
+            }
+
+            Node::Timestamp(time, style) => {
+                if let Some(timestamp) = time.timestamp(context) {
+                    match style {
+                        Some(style) => output.push_str(&format!("<t:{}:{}>", timestamp, style)),
+                        None => output.push_str(&format!("<t:{}>", timestamp)),
+                    }
+                }
+            }
+
+            Node::TimeFrom(time, format) => {
+                let difference = (context.now.timestamp() - time).unsigned_abs();
+
+                output.push_str(&fmt_displacement(format, difference));
+            }
+
+            Node::TimeNow(timezone, format) => {
+                output.push_str(&context.now.with_timezone(timezone).format(format).to_string());
+            }
+
+            Node::If(condition, then, otherwise) => {
+                if condition.holds(context) {
+                    render_nodes(then, context, output);
+                } else {
+                    render_nodes(otherwise, context, output);
+                }
+            }
+        }
+    }
+}
+
+fn fmt_displacement(format: &str, seconds: u64) -> String {
+    let mut seconds = seconds;
+    let mut days: u64 = 0;
+    let mut hours: u64 = 0;
+    let mut minutes: u64 = 0;
+
+    for (rep, time_type, div) in
+        [("%d", &mut days, 86400), ("%h", &mut hours, 3600), ("%m", &mut minutes, 60)].iter_mut()
+    {
+        if format.contains(*rep) {
+            let (divided, new_seconds) = seconds.div_rem(&div);
+
+            **time_type = divided;
+            seconds = new_seconds;
+        }
+    }
+
+    format
+        .replace("%s", &seconds.to_string())
+        .replace("%m", &minutes.to_string())
+        .replace("%h", &hours.to_string())
+        .replace("%d", &days.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn context() -> TemplateContext {
+        TemplateContext {
+            channel: Some(1),
+            guild: Some("Guild".to_string()),
+            creator: Some(2),
+            occurrence: 3,
+            max_occurrences: Some(3),
+            next: Some(Utc.timestamp_opt(1_700_000_000, 0).unwrap()),
+            now: Utc.timestamp_opt(1_600_000_000, 0).unwrap(),
+        }
+    }
+
+    fn render(template: &str) -> String {
+        parse_template(template).unwrap().render(&context())
+    }
+
+    #[test]
+    fn replaces_every_occurrence_of_tag() {
+        assert_eq!(render("<<occurrence>> of <<max_occurrences>>, <<occurrence>>"), "3 of 3, 3");
+        assert_eq!(render("<<channel>><<channel>>"), "<#1><#1>");
+    }
+
+    #[test]
+    fn nested_if_else_end() {
+        let template = "<<if next>><<if occurrence == max_occurrences>>last<<else>>more<<end>> \
+                        <<else>>none<<end>>";
+
+        assert_eq!(render(template), "last ");
+        assert_eq!(render("<<if not guild>>a<<else>><<if occurrence < 2>>b<<end>>c<<end>>"), "c");
+    }
+
+    #[test]
+    fn unterminated_if_and_stray_tags_fail() {
+        assert!(parse_template("<<if next>>text").is_err());
+        assert!(parse_template("<<if next>>a<<else>>b").is_err());
+        assert!(parse_template("<<if next>>a<<else>>b<<else>>c<<end>>").is_err());
+        assert!(parse_template("text<<end>>").is_err());
+        assert!(parse_template("text<<else>>").is_err());
+    }
+
+    #[test]
+    fn unknown_tags_are_text() {
+        assert_eq!(render("<<word>> and <<foo:bar>>"), "<<word>> and <<foo:bar>>");
+        assert_eq!(render("<<word>><<occurrence>>"), "<<word>>3");
+    }
+
+    #[test]
+    fn malformed_time_arguments_fail() {
+        assert!(parse_template("<<timestamp:soon>>").is_err());
+        assert!(parse_template("<<timestamp:now+soon>>").is_err());
+        assert!(parse_template("<<timestamp:now:X>>").is_err());
+        assert!(parse_template("<<timefrom:yesterday:%d>>").is_err());
+        assert!(parse_template("<<timefrom:1600000000>>").is_err());
+        assert!(parse_template("<<timenow:Nowhere/City:%H>>").is_err());
+    }
+
+    #[test]
+    fn time_tags() {
+        assert_eq!(render("<<timestamp:now+300:R>>"), "<t:1600000300:R>");
+        assert_eq!(render("<<timestamp:next-60>>"), "<t:1699999940>");
+        assert_eq!(render("<<timefrom:1599913600:%d days %h hours>>"), "1 days 0 hours");
+    }
+
+    #[test]
+    fn literal_angle_brackets_are_text() {
+        for text in ["a << b", "<<<", "<< word >>", "<<1>>", "x <<word", ">> <<"] {
+            assert_eq!(render(text), text);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use chrono::{TimeZone, Utc};
 use chrono_tz::TZ_VARIANTS;
 use poise::AutocompleteChoice;
 
@@ -115,3 +116,51 @@ pub async fn time_hint_autocomplete(
         }
     }
 }
+
+pub async fn repeating_reminder_autocomplete(
+    ctx: Context<'_>,
+    partial: &str,
+) -> Vec<AutocompleteChoice<String>> {
+    let timezone = ctx.timezone().await;
+
+    sqlx::query!(
+        "
+SELECT reminders.uid, reminders.name, reminders.content, reminders.utc_time
+FROM reminders
+INNER JOIN channels ON channels.id = reminders.channel_id
+WHERE
+    (channels.guild_id = (SELECT id FROM guilds WHERE guild = ?) OR channels.channel = ?)
+    AND (
+        reminders.interval_seconds IS NOT NULL
+        OR reminders.interval_months IS NOT NULL
+        OR reminders.cron IS NOT NULL
+        OR reminders.rrule IS NOT NULL
+    )
+    AND (reminders.content LIKE CONCAT('%', ?, '%') OR reminders.name LIKE CONCAT('%', ?, '%'))
+ORDER BY reminders.utc_time
+LIMIT 25",
+        ctx.guild_id().map(|g| g.0),
+        ctx.channel_id().0,
+        partial,
+        partial,
+    )
+    .fetch_all(&ctx.data().database)
+    .await
+    .unwrap_or_default()
+    .into_iter()
+    .map(|row| {
+        let label = if row.content.is_empty() { row.name } else { row.content };
+
+        AutocompleteChoice {
+            name: format!(
+                "{} ({})",
+                label.chars().take(60).collect::<String>().replace('\n', " "),
+                Utc.from_utc_datetime(&row.utc_time)
+                    .with_timezone(&timezone)
+                    .format("%Y-%m-%d %H:%M")
+            ),
+            value: row.uid,
+        }
+    })
+    .collect()
+}
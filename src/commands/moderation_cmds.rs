@@ -2,6 +2,7 @@ use chrono::offset::Utc;
 use chrono_tz::{Tz, TZ_VARIANTS};
 use levenshtein::levenshtein;
 use log::warn;
+use poise::serenity_prelude::model::channel::Channel;
 
 use super::autocomplete::timezone_autocomplete;
 use crate::{consts::THEME_COLOR, models::CtxData, Context, Error};
@@ -148,6 +149,97 @@ pub async fn unset_allowed_dm(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+/// Set a channel to be told when reminders are removed automatically
+#[poise::command(
+    slash_command,
+    rename = "logchannel",
+    guild_only = true,
+    default_member_permissions = "MANAGE_GUILD",
+    identifying_name = "log_channel"
+)]
+pub async fn log_channel(
+    ctx: Context<'_>,
+    #[description = "Channel for notices. Leave blank to send them to reminder creators by DM"]
+    channel: Option<Channel>,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+
+    sqlx::query!(
+        "UPDATE guilds SET log_channel = ? WHERE guild = ?",
+        channel.as_ref().map(|c| c.id().0),
+        guild_id.0
+    )
+    .execute(&ctx.data().database)
+    .await?;
+
+    match channel {
+        Some(channel) => {
+            ctx.say(format!("Notices about removed reminders will be sent to <#{}>", channel.id()))
+                .await?;
+        }
+        None => {
+            ctx.say("Notices about removed reminders will be sent to reminder creators by DM")
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(poise::ChoiceParameter)]
+pub enum MissedPolicy {
+    #[name = "Send once, late"]
+    Once,
+    #[name = "Send every missed occurrence"]
+    All,
+    #[name = "Skip reminders that are too late"]
+    Skip,
+    #[name = "Post a summary of missed reminders"]
+    Digest,
+}
+
+impl MissedPolicy {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MissedPolicy::Once => "once",
+            MissedPolicy::All => "all",
+            MissedPolicy::Skip => "skip",
+            MissedPolicy::Digest => "digest",
+        }
+    }
+}
+
+/// Choose what happens to reminders that were due while the bot was offline
+#[poise::command(
+    slash_command,
+    rename = "missed",
+    guild_only = true,
+    default_member_permissions = "MANAGE_GUILD",
+    identifying_name = "missed_policy"
+)]
+pub async fn missed_policy(
+    ctx: Context<'_>,
+    #[description = "What to do with missed reminders"] policy: MissedPolicy,
+) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+
+    sqlx::query!(
+        "UPDATE guilds SET missed_policy = ? WHERE guild = ?",
+        policy.as_str(),
+        guild_id.0
+    )
+    .execute(&ctx.data().database)
+    .await?;
+
+    ctx.say(format!(
+        "Missed reminders will now be handled with the **{}** policy",
+        policy.as_str()
+    ))
+    .await?;
+
+    Ok(())
+}
+
 /// View the webhook being used to send reminders to this channel
 #[poise::command(
     slash_command,
@@ -1,27 +1,38 @@
 use std::{collections::HashSet, string::ToString};
 
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
 use chrono_tz::Tz;
 use num_integer::Integer;
 use poise::{
     serenity_prelude::{
-        builder::CreateEmbed, component::ButtonStyle, model::channel::Channel, ReactionType,
+        builder::CreateEmbed, component::ButtonStyle, model::channel::Channel, ChannelId,
+        ReactionType, User,
     },
     CreateReply, Modal,
 };
+use postman::{
+    schedule::{
+        add_months_anchored, fetch_holidays, next_business_day, parse_cron, validate_rrule,
+    },
+    template::parse_template,
+};
+use serde_json::json;
 
 use crate::{
-    commands::autocomplete::{time_hint_autocomplete, timezone_autocomplete},
+    commands::autocomplete::{
+        repeating_reminder_autocomplete, time_hint_autocomplete, timezone_autocomplete,
+    },
     component_models::{
         pager::{DelPager, LookPager, Pager},
         ComponentDataModel, DelSelector, UndoReminder,
     },
     consts::{
-        EMBED_DESCRIPTION_MAX_LENGTH, HOUR, MINUTE, REGEX_CHANNEL_USER, SELECT_MAX_ENTRIES,
-        THEME_COLOR,
+        EMBED_DESCRIPTION_MAX_LENGTH, HOUR, MINUTE, REGEX_CHANNEL_USER, REGEX_USER_ROLE,
+        SELECT_MAX_ENTRIES, THEME_COLOR,
     },
     interval_parser::parse_duration,
     models::{
+        delivery::Delivery,
         reminder::{
             builder::{MultiReminderBuilder, ReminderScope},
             content::Content,
@@ -189,6 +200,131 @@ pub async fn nudge(
     Ok(())
 }
 
+/// Merge reminders due in the same minute on this channel into a single message
+#[poise::command(
+    slash_command,
+    identifying_name = "digest",
+    default_member_permissions = "MANAGE_GUILD"
+)]
+pub async fn digest(
+    ctx: Context<'_>,
+    #[description = "Whether to merge reminders sent at the same time"] enabled: bool,
+) -> Result<(), Error> {
+    let mut channel_data = ctx.channel_data().await.unwrap();
+
+    channel_data.digest = enabled;
+    channel_data.commit_changes(&ctx.data().database).await;
+
+    if enabled {
+        ctx.say("Reminders due in the same minute on this channel will be sent as one message")
+            .await?;
+    } else {
+        ctx.say("Reminders on this channel will be sent individually").await?;
+    }
+
+    Ok(())
+}
+
+/// Skip the next occurrence of a repeating reminder, or all occurrences on a later date
+#[poise::command(
+    slash_command,
+    identifying_name = "skip",
+    default_member_permissions = "MANAGE_GUILD"
+)]
+pub async fn skip(
+    ctx: Context<'_>,
+    #[description = "The repeating reminder to skip"]
+    #[autocomplete = "repeating_reminder_autocomplete"]
+    reminder: String,
+    #[description = "A later date to skip instead of the next occurrence"]
+    #[autocomplete = "time_hint_autocomplete"]
+    date: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer().await?;
+
+    let row = sqlx::query!(
+        "
+SELECT reminders.id, reminders.timezone, reminders.utc_time
+FROM reminders
+INNER JOIN channels ON channels.id = reminders.channel_id
+WHERE
+    reminders.uid = ?
+    AND (channels.guild_id = (SELECT id FROM guilds WHERE guild = ?) OR channels.channel = ?)
+    AND (
+        reminders.interval_seconds IS NOT NULL
+        OR reminders.interval_months IS NOT NULL
+        OR reminders.cron IS NOT NULL
+        OR reminders.rrule IS NOT NULL
+    )",
+        reminder,
+        ctx.guild_id().map(|g| g.0),
+        ctx.channel_id().0
+    )
+    .fetch_optional(&ctx.data().database)
+    .await?;
+
+    let row = match row {
+        Some(row) => row,
+
+        None => {
+            ctx.say(
+                "Could not find a repeating reminder by that name. Please choose one from the list",
+            )
+            .await?;
+
+            return Ok(());
+        }
+    };
+
+    match date {
+        None => {
+            sqlx::query!("UPDATE reminders SET skip_next = 1 WHERE id = ?", row.id)
+                .execute(&ctx.data().database)
+                .await?;
+
+            ctx.say(format!(
+                "The occurrence at <t:{}:f> will be skipped",
+                Utc.from_utc_datetime(&row.utc_time).timestamp()
+            ))
+            .await?;
+        }
+
+        Some(date) => {
+            let timezone = row.timezone.parse::<Tz>().unwrap_or(Tz::UTC);
+
+            match natural_parser(&date, &timezone.to_string())
+                .await
+                .and_then(|timestamp| Utc.timestamp_opt(timestamp, 0).single())
+            {
+                Some(time) => {
+                    let date = time.with_timezone(&timezone).format("%Y-%m-%d").to_string();
+
+                    sqlx::query!(
+                        "
+UPDATE reminders
+SET exclude_dates = JSON_ARRAY_APPEND(IFNULL(exclude_dates, JSON_ARRAY()), '$', ?)
+WHERE id = ? AND NOT JSON_CONTAINS(IFNULL(exclude_dates, JSON_ARRAY()), JSON_QUOTE(?))",
+                        date,
+                        row.id,
+                        date
+                    )
+                    .execute(&ctx.data().database)
+                    .await?;
+
+                    ctx.say(format!("Occurrences on {} will be skipped", date)).await?;
+                }
+
+                None => {
+                    ctx.say("Date could not be processed. Please make it as clear as possible")
+                        .await?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// View reminders on a specific channel
 #[poise::command(
     slash_command,
@@ -283,6 +419,71 @@ pub async fn look(
     Ok(())
 }
 
+/// View reminders recently sent to a specific channel
+#[poise::command(
+    slash_command,
+    identifying_name = "history",
+    default_member_permissions = "MANAGE_GUILD"
+)]
+pub async fn history(
+    ctx: Context<'_>,
+    #[description = "Channel to view sent reminders on"] channel: Option<Channel>,
+) -> Result<(), Error> {
+    let timezone = ctx.timezone().await;
+
+    let channel_opt = ctx.channel_id().to_channel_cached(&ctx.discord());
+
+    let channel_id = if let Some(Channel::Guild(current)) = channel_opt {
+        if Some(current.guild_id) == ctx.guild_id() {
+            channel.map_or_else(|| ctx.channel_id(), |c| c.id())
+        } else {
+            ctx.channel_id()
+        }
+    } else {
+        ctx.channel_id()
+    };
+
+    let channel_name =
+        if let Some(Channel::Guild(channel)) = channel_id.to_channel_cached(&ctx.discord()) {
+            Some(channel.name)
+        } else {
+            None
+        };
+
+    let deliveries = Delivery::from_channel(&ctx.data().database, channel_id, 25).await?;
+
+    if deliveries.is_empty() {
+        let _ = ctx.say("No reminders have been sent to the specified channel").await;
+    } else {
+        let mut char_count = 0;
+
+        let display = deliveries
+            .iter()
+            .map(|delivery| delivery.display(ctx.guild_id(), &timezone))
+            .take_while(|p| {
+                char_count += p.len();
+
+                char_count < EMBED_DESCRIPTION_MAX_LENGTH
+            })
+            .collect::<Vec<String>>()
+            .join("");
+
+        ctx.send(|r| {
+            r.ephemeral(true).embed(|e| {
+                e.title(format!(
+                    "Sent reminders{}",
+                    channel_name.map_or(String::new(), |n| format!(" on #{}", n))
+                ))
+                .description(display)
+                .color(*THEME_COLOR)
+            })
+        })
+        .await?;
+    }
+
+    Ok(())
+}
+
 /// Delete reminders
 #[poise::command(
     slash_command,
@@ -552,6 +753,180 @@ pub async fn delete_timer(
     Ok(())
 }
 
+#[derive(poise::ChoiceParameter)]
+pub enum MonthAnchor {
+    #[name = "Same day, or the last day of shorter months"]
+    Clamp,
+    #[name = "Same weekday of the month, e.g. the first Monday"]
+    NthWeekday,
+    #[name = "Last of the same weekday, e.g. the last Friday"]
+    LastWeekday,
+    #[name = "Last day of the month"]
+    LastDay,
+}
+
+impl MonthAnchor {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MonthAnchor::Clamp => "clamp",
+            MonthAnchor::NthWeekday => "nth_weekday",
+            MonthAnchor::LastWeekday => "last_weekday",
+            MonthAnchor::LastDay => "last_day",
+        }
+    }
+}
+
+#[derive(poise::ChoiceParameter)]
+pub enum ReplaceMode {
+    #[name = "Edit the previous message"]
+    Edit,
+    #[name = "Delete the previous message and send a new one"]
+    Repost,
+}
+
+impl ReplaceMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReplaceMode::Edit => "edit",
+            ReplaceMode::Repost => "repost",
+        }
+    }
+}
+
+#[derive(poise::ChoiceParameter)]
+pub enum MentionMode {
+    #[name = "Users, roles, @everyone and @here"]
+    Everyone,
+    #[name = "Users and roles"]
+    UsersAndRoles,
+    #[name = "Users only"]
+    Users,
+    #[name = "Nobody"]
+    Nobody,
+}
+
+impl MentionMode {
+    fn parse(&self) -> &'static [&'static str] {
+        match self {
+            MentionMode::Everyone => &["users", "roles", "everyone"],
+            MentionMode::UsersAndRoles => &["users", "roles"],
+            MentionMode::Users => &["users"],
+            MentionMode::Nobody => &[],
+        }
+    }
+}
+
+/// The allowed mentions to store with a reminder, as Discord's JSON object, or `None` to ping
+/// anything the content mentions. Users without the Mention Everyone permission can't make a
+/// reminder ping @everyone or @here, so their reminders are limited to users and roles.
+fn allowed_mentions_json(
+    mode: Option<MentionMode>,
+    allowlist: Option<&str>,
+    can_mention_everyone: bool,
+) -> Result<Option<String>, &'static str> {
+    if matches!(mode, Some(MentionMode::Everyone)) && !can_mention_everyone {
+        return Err("You need the Mention Everyone permission to let a reminder ping @everyone");
+    }
+
+    let mut parse = match (&mode, allowlist) {
+        (Some(mode), _) => mode.parse().to_vec(),
+        (None, Some(_)) => vec![],
+        (None, None) if can_mention_everyone => return Ok(None),
+        (None, None) => MentionMode::UsersAndRoles.parse().to_vec(),
+    };
+
+    let (mut users, mut roles) = (vec![], vec![]);
+
+    if let Some(allowlist) = allowlist {
+        for mention in REGEX_USER_ROLE.captures_iter(allowlist) {
+            match mention.get(1).map(|m| m.as_str()) {
+                Some("&") => roles.push(mention[2].to_string()),
+                _ => users.push(mention[2].to_string()),
+            }
+        }
+
+        if users.is_empty() && roles.is_empty() {
+            return Err("`mention_allowlist` must be a list of role or user mentions");
+        }
+
+        // Discord rejects a type that is both parsed and listed
+        if !users.is_empty() {
+            parse.retain(|p| *p != "users");
+        }
+        if !roles.is_empty() {
+            parse.retain(|p| *p != "roles");
+        }
+
+        users.sort();
+        users.dedup();
+        roles.sort();
+        roles.dedup();
+
+        if users.len() > 100 || roles.len() > 100 {
+            return Err("`mention_allowlist` can list at most 100 users and 100 roles");
+        }
+    }
+
+    Ok(Some(json!({ "parse": parse, "users": users, "roles": roles }).to_string()))
+}
+
+/// Whether the author may mention @everyone in every channel a reminder is being set in. Threads
+/// take their parent's overwrites. Anything missing from the cache is fetched over HTTP.
+async fn can_mention_everyone(ctx: Context<'_>, scopes: &[ReminderScope]) -> bool {
+    let guild_id = match ctx.guild_id() {
+        Some(guild_id) => guild_id,
+        None => return true,
+    };
+
+    let member = match guild_id.member(&ctx.discord(), ctx.author().id).await {
+        Ok(member) => member,
+        Err(_) => return false,
+    };
+
+    let guild = ctx.guild();
+    let partial_guild = match guild {
+        Some(_) => None,
+        None => match ctx.discord().http.get_guild(guild_id.0).await {
+            Ok(guild) => Some(guild),
+            Err(_) => return false,
+        },
+    };
+
+    for scope in scopes {
+        let channel_id = match scope {
+            ReminderScope::Channel(id) => ChannelId(*id),
+            ReminderScope::User(_) => continue,
+        };
+
+        let mut channel = match channel_id.to_channel(&ctx.discord()).await {
+            Ok(Channel::Guild(channel)) => channel,
+            _ => return false,
+        };
+
+        if channel.thread_metadata.is_some() {
+            channel = match channel.parent_id.map(|p| p.to_channel(&ctx.discord())) {
+                Some(parent) => match parent.await {
+                    Ok(Channel::Guild(parent)) => parent,
+                    _ => return false,
+                },
+                None => return false,
+            };
+        }
+
+        let permissions = match (&guild, &partial_guild) {
+            (Some(guild), _) => guild.user_permissions_in(&channel, &member),
+            (_, Some(guild)) => guild.user_permissions_in(&channel, &member),
+            (None, None) => return false,
+        };
+
+        if !permissions.map_or(false, |p| p.mention_everyone()) {
+            return false;
+        }
+    }
+
+    true
+}
+
 #[derive(poise::Modal)]
 #[name = "Reminder"]
 struct ContentModal {
@@ -573,16 +948,51 @@ pub async fn multiline(
     #[description = "A description of the time to set the reminder for"]
     #[autocomplete = "time_hint_autocomplete"]
     time: String,
-    #[description = "Channel or user mentions to set the reminder for"] channels: Option<String>,
-    #[description = "(Patreon only) Time to wait before repeating the reminder. Leave blank for one-shot reminder"]
+    #[description = "Channel, thread or user mentions to set the reminder for"] channels: Option<
+        String,
+    >,
+    #[description = "(Patreon only) Time to wait before repeating, e.g. `1 day` or `weekdays`. Blank for one-shot"]
     interval: Option<String>,
     #[description = "(Patreon only) For repeating reminders, the time at which the reminder will stop repeating"]
     expires: Option<String>,
+    #[description = "(Patreon only) For repeating reminders, the number of times to send before stopping"]
+    #[min = 1]
+    max_occurrences: Option<u32>,
     #[description = "Set the TTS flag on the reminder message, similar to the /tts command"]
     tts: Option<bool>,
     #[description = "Set a timezone override for this reminder only"]
     #[autocomplete = "timezone_autocomplete"]
     timezone: Option<String>,
+    #[description = "(Patreon only) Cron expression to repeat on instead of an interval, e.g. `0 9 * * 1-5`"]
+    cron: Option<String>,
+    #[description = "(Patreon only) iCalendar RRULE to repeat on, e.g. `FREQ=MONTHLY;BYDAY=1MO`"]
+    rrule: Option<String>,
+    #[description = "(Patreon only) For monthly intervals, the day to repeat on each month"]
+    month_anchor: Option<MonthAnchor>,
+    #[description = "(Patreon only) For hourly intervals, keep the same local times when the clocks change"]
+    keep_time: Option<bool>,
+    #[description = "(Patreon only) For repeating reminders, replace the previous message on each repeat"]
+    replace_previous: Option<ReplaceMode>,
+    #[description = "Delete the sent message after this long, e.g. `5 minutes`"]
+    delete_after: Option<String>,
+    #[description = "Open a new thread or forum post with this title each time the reminder is sent"]
+    thread_title: Option<String>,
+    #[description = "Add a \"Mark done\" button that records who has handled the reminder"]
+    ack_button: Option<bool>,
+    #[description = "Add buttons to snooze the reminder for 10 minutes, an hour or until tomorrow"]
+    snooze_buttons: Option<bool>,
+    #[description = "Escalate if nobody marks the reminder done within this long, e.g. `15 minutes`"]
+    escalate_after: Option<String>,
+    #[description = "Role and user mentions to ping when escalating"] escalate_to: Option<String>,
+    #[description = "A backup user to direct message when escalating"] escalate_dm: Option<User>,
+    #[description = "The number of times to escalate before giving up (default 1)"]
+    #[min = 1]
+    #[max = 10]
+    escalate_limit: Option<u32>,
+    #[description = "Who the reminder may ping. Defaults to everything you have permission to ping"]
+    mentions: Option<MentionMode>,
+    #[description = "Only ping these roles and users, as well as any allowed by `mentions`"]
+    mention_allowlist: Option<String>,
 ) -> Result<(), Error> {
     let tz = timezone.map(|t| t.parse::<Tz>().ok()).flatten();
     let data = ContentModal::execute(ctx).await?;
@@ -594,8 +1004,24 @@ pub async fn multiline(
         channels,
         interval,
         expires,
+        max_occurrences,
         tts,
         tz,
+        cron,
+        rrule,
+        month_anchor,
+        keep_time,
+        replace_previous,
+        delete_after,
+        thread_title,
+        ack_button,
+        snooze_buttons,
+        escalate_after,
+        escalate_to,
+        escalate_dm,
+        escalate_limit,
+        mentions,
+        mention_allowlist,
     )
     .await
 }
@@ -612,21 +1038,81 @@ pub async fn remind(
     #[autocomplete = "time_hint_autocomplete"]
     time: String,
     #[description = "The message content to send"] content: String,
-    #[description = "Channel or user mentions to set the reminder for"] channels: Option<String>,
-    #[description = "(Patreon only) Time to wait before repeating the reminder. Leave blank for one-shot reminder"]
+    #[description = "Channel, thread or user mentions to set the reminder for"] channels: Option<
+        String,
+    >,
+    #[description = "(Patreon only) Time to wait before repeating, e.g. `1 day` or `weekdays`. Blank for one-shot"]
     interval: Option<String>,
     #[description = "(Patreon only) For repeating reminders, the time at which the reminder will stop repeating"]
     expires: Option<String>,
+    #[description = "(Patreon only) For repeating reminders, the number of times to send before stopping"]
+    #[min = 1]
+    max_occurrences: Option<u32>,
     #[description = "Set the TTS flag on the reminder message, similar to the /tts command"]
     tts: Option<bool>,
     #[description = "Set a timezone override for this reminder only"]
     #[autocomplete = "timezone_autocomplete"]
     timezone: Option<String>,
+    #[description = "(Patreon only) Cron expression to repeat on instead of an interval, e.g. `0 9 * * 1-5`"]
+    cron: Option<String>,
+    #[description = "(Patreon only) iCalendar RRULE to repeat on, e.g. `FREQ=MONTHLY;BYDAY=1MO`"]
+    rrule: Option<String>,
+    #[description = "(Patreon only) For monthly intervals, the day to repeat on each month"]
+    month_anchor: Option<MonthAnchor>,
+    #[description = "(Patreon only) For hourly intervals, keep the same local times when the clocks change"]
+    keep_time: Option<bool>,
+    #[description = "(Patreon only) For repeating reminders, replace the previous message on each repeat"]
+    replace_previous: Option<ReplaceMode>,
+    #[description = "Delete the sent message after this long, e.g. `5 minutes`"]
+    delete_after: Option<String>,
+    #[description = "Open a new thread or forum post with this title each time the reminder is sent"]
+    thread_title: Option<String>,
+    #[description = "Add a \"Mark done\" button that records who has handled the reminder"]
+    ack_button: Option<bool>,
+    #[description = "Add buttons to snooze the reminder for 10 minutes, an hour or until tomorrow"]
+    snooze_buttons: Option<bool>,
+    #[description = "Escalate if nobody marks the reminder done within this long, e.g. `15 minutes`"]
+    escalate_after: Option<String>,
+    #[description = "Role and user mentions to ping when escalating"] escalate_to: Option<String>,
+    #[description = "A backup user to direct message when escalating"] escalate_dm: Option<User>,
+    #[description = "The number of times to escalate before giving up (default 1)"]
+    #[min = 1]
+    #[max = 10]
+    escalate_limit: Option<u32>,
+    #[description = "Who the reminder may ping. Defaults to everything you have permission to ping"]
+    mentions: Option<MentionMode>,
+    #[description = "Only ping these roles and users, as well as any allowed by `mentions`"]
+    mention_allowlist: Option<String>,
 ) -> Result<(), Error> {
     let tz = timezone.map(|t| t.parse::<Tz>().ok()).flatten();
 
-    create_reminder(Context::Application(ctx), time, content, channels, interval, expires, tts, tz)
-        .await
+    create_reminder(
+        Context::Application(ctx),
+        time,
+        content,
+        channels,
+        interval,
+        expires,
+        max_occurrences,
+        tts,
+        tz,
+        cron,
+        rrule,
+        month_anchor,
+        keep_time,
+        replace_previous,
+        delete_after,
+        thread_title,
+        ack_button,
+        snooze_buttons,
+        escalate_after,
+        escalate_to,
+        escalate_dm,
+        escalate_limit,
+        mentions,
+        mention_allowlist,
+    )
+    .await
 }
 
 async fn create_reminder(
@@ -636,11 +1122,60 @@ async fn create_reminder(
     channels: Option<String>,
     interval: Option<String>,
     expires: Option<String>,
+    max_occurrences: Option<u32>,
     tts: Option<bool>,
     timezone: Option<Tz>,
+    cron: Option<String>,
+    rrule: Option<String>,
+    month_anchor: Option<MonthAnchor>,
+    keep_time: Option<bool>,
+    replace_previous: Option<ReplaceMode>,
+    delete_after: Option<String>,
+    thread_title: Option<String>,
+    ack_button: Option<bool>,
+    snooze_buttons: Option<bool>,
+    escalate_after: Option<String>,
+    escalate_to: Option<String>,
+    escalate_dm: Option<User>,
+    escalate_limit: Option<u32>,
+    mentions: Option<MentionMode>,
+    mention_allowlist: Option<String>,
 ) -> Result<(), Error> {
-    if interval.is_none() && expires.is_some() {
-        ctx.say("`expires` can only be used with `interval`").await?;
+    let repeats = [interval.is_some(), cron.is_some(), rrule.is_some()];
+
+    if !repeats.contains(&true)
+        && (expires.is_some() || max_occurrences.is_some() || replace_previous.is_some())
+    {
+        ctx.say(
+            "`expires`, `max_occurrences` and `replace_previous` can only be used with \
+             `interval`, `cron` or `rrule`",
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    if repeats.iter().filter(|r| **r).count() > 1 {
+        ctx.say("Only one of `interval`, `cron` and `rrule` can be used").await?;
+
+        return Ok(());
+    }
+
+    if escalate_after.is_none()
+        && (escalate_to.is_some() || escalate_dm.is_some() || escalate_limit.is_some())
+    {
+        ctx.say(
+            "`escalate_to`, `escalate_dm` and `escalate_limit` can only be used with \
+             `escalate_after`",
+        )
+        .await?;
+
+        return Ok(());
+    }
+
+    if escalate_after.is_some() && escalate_to.is_none() && escalate_dm.is_none() {
+        ctx.say("`escalate_after` needs `escalate_to` or `escalate_dm` to say who to escalate to")
+            .await?;
 
         return Ok(());
     }
@@ -674,15 +1209,17 @@ async fn create_reminder(
                 }
             };
 
-            let (processed_interval, processed_expires) = if let Some(repeat) = &interval {
+            let (processed_interval, processed_expires) = if repeats.contains(&true) {
                 if check_subscription(&ctx.discord(), ctx.author().id).await
                     || (ctx.guild_id().is_some()
                         && check_guild_subscription(&ctx.discord(), ctx.guild_id().unwrap()).await)
                 {
                     (
-                        parse_duration(repeat)
-                            .or_else(|_| parse_duration(&format!("1 {}", repeat)))
-                            .ok(),
+                        interval.as_ref().and_then(|repeat| {
+                            parse_duration(repeat)
+                                .or_else(|_| parse_duration(&format!("1 {}", repeat)))
+                                .ok()
+                        }),
                         {
                             if let Some(arg) = &expires {
                                 natural_parser(arg, &timezone.to_string()).await
@@ -703,11 +1240,116 @@ async fn create_reminder(
                 (None, None)
             };
 
+            let processed_delete_after = delete_after.as_ref().and_then(|arg| {
+                parse_duration(arg)
+                    .ok()
+                    .filter(|d| d.month == 0 && d.sec > 0 && d.sec <= u32::MAX as u64)
+                    .map(|d| d.sec as u32)
+            });
+
+            let processed_escalate_after = escalate_after.as_ref().and_then(|arg| {
+                parse_duration(arg)
+                    .ok()
+                    .filter(|d| d.month == 0 && d.sec > 0 && d.sec <= u32::MAX as u64)
+                    .map(|d| d.sec as u32)
+            });
+
+            let escalate_mentions = escalate_to.as_ref().and_then(|arg| parse_role_user_list(arg));
+
+            let allowed_mentions = allowed_mentions_json(
+                mentions,
+                mention_allowlist.as_deref(),
+                can_mention_everyone(ctx, &scopes).await,
+            );
+
+            // cron reminders start on the first occurrence from the given time
+            let schedule = cron.as_ref().map(|c| parse_cron(c));
+            let time = match &schedule {
+                Some(Ok(schedule)) => Utc
+                    .timestamp_opt(time - 1, 0)
+                    .single()
+                    .and_then(|t| schedule.after(&t.with_timezone(&timezone)).next())
+                    .map_or(time, |t| t.timestamp()),
+                _ => time,
+            };
+
+            // as do RRULE reminders, keeping the given time of day
+            let rule = rrule.as_ref().map(|r| validate_rrule(r));
+            let first_occurrence = match &rule {
+                Some(Ok(rule)) => Some(
+                    Utc.timestamp_opt(time, 0)
+                        .single()
+                        .and_then(|t| rule.following(&t.with_timezone(&timezone), true)),
+                ),
+                _ => None,
+            };
+            let time = first_occurrence.flatten().map_or(time, |t| t.timestamp());
+
+            // anchored monthly reminders start on their anchor day
+            let monthly = processed_interval.map_or(false, |i| i.month > 0);
+            let time = match &month_anchor {
+                Some(anchor) if monthly => Utc
+                    .timestamp_opt(time, 0)
+                    .single()
+                    .and_then(|t| {
+                        add_months_anchored(&t.with_timezone(&timezone), 0, anchor.as_str(), None)
+                    })
+                    .map_or(time, |t| t.timestamp()),
+                _ => time,
+            };
+
+            // weekday reminders start on a business day
+            let time = match processed_interval {
+                Some(interval) if interval.weekdays => {
+                    let holidays = match ctx.guild_id() {
+                        Some(guild_id) => fetch_holidays(&ctx.data().database, guild_id.0).await,
+                        None => vec![],
+                    };
+
+                    Utc.timestamp_opt(time, 0).single().map_or(time, |t| {
+                        next_business_day(t.with_timezone(&timezone), &holidays).timestamp()
+                    })
+                }
+                _ => time,
+            };
+
             if processed_interval.is_none() && interval.is_some() {
                 ctx.say(
                     "Repeat interval could not be processed. Try similar to `1 hour` or `4 days`",
                 )
                 .await?;
+            } else if let Some(Err(e)) = &schedule {
+                ctx.say(format!("Cron expression could not be processed: {}", e)).await?;
+            } else if let Some(Err(e)) = &rule {
+                ctx.say(format!("RRULE could not be processed: {}", e)).await?;
+            } else if let Some(None) = first_occurrence {
+                ctx.say("RRULE has no occurrences after the given time").await?;
+            } else if month_anchor.is_some() && !monthly {
+                ctx.say("`month_anchor` can only be used with intervals in months, e.g. `1 month`")
+                    .await?;
+            } else if keep_time.is_some() && interval.is_none() {
+                ctx.say("`keep_time` can only be used with `interval`").await?;
+            } else if thread_title.as_ref().map_or(false, |t| t.chars().count() > 100) {
+                ctx.say("`thread_title` must be 100 characters or fewer").await?;
+            } else if processed_delete_after.is_none() && delete_after.is_some() {
+                ctx.say(
+                    "`delete_after` could not be processed. Try similar to `5 minutes` or `2 hours`",
+                )
+                .await?;
+            } else if processed_escalate_after.is_none() && escalate_after.is_some() {
+                ctx.say(
+                    "`escalate_after` could not be processed. Try similar to `15 minutes` or \
+                     `1 hour`",
+                )
+                .await?;
+            } else if escalate_mentions.as_ref().map_or(escalate_to.is_some(), |m| m.len() > 1000) {
+                ctx.say("`escalate_to` must be a list of role or user mentions").await?;
+            } else if let Err(e) = &allowed_mentions {
+                ctx.say(*e).await?;
+            } else if let Err(e) = parse_template(&content.content) {
+                ctx.say(format!("Content could not be processed: {}", e)).await?;
+            } else if let Some(Err(e)) = thread_title.as_ref().map(|t| parse_template(t)) {
+                ctx.say(format!("`thread_title` could not be processed: {}", e)).await?;
             } else if processed_expires.is_none() && expires.is_some() {
                 ctx.say("Expiry time failed to process. Please make it as clear as possible")
                     .await?;
@@ -718,7 +1360,22 @@ async fn create_reminder(
                     .time(time)
                     .timezone(timezone)
                     .expires(processed_expires)
-                    .interval(processed_interval);
+                    .max_occurrences(max_occurrences)
+                    .interval(processed_interval)
+                    .interval_wallclock(keep_time.unwrap_or(false))
+                    .cron(cron)
+                    .rrule(rrule)
+                    .month_anchor(month_anchor.map(|a| a.as_str().to_string()))
+                    .replace_mode(replace_previous.map(|r| r.as_str().to_string()))
+                    .delete_after(processed_delete_after)
+                    .thread_title(thread_title)
+                    .ack_button(ack_button.unwrap_or(false) || processed_escalate_after.is_some())
+                    .snooze_buttons(snooze_buttons.unwrap_or(false))
+                    .escalate_after(processed_escalate_after)
+                    .escalate_mentions(escalate_mentions)
+                    .escalate_user(escalate_dm.map(|u| u.id.0))
+                    .escalate_limit(escalate_limit.unwrap_or(1))
+                    .allowed_mentions(allowed_mentions.unwrap());
 
                 builder.set_scopes(scopes);
 
@@ -776,7 +1433,7 @@ async fn create_reminder(
     Ok(())
 }
 
-fn create_response(
+pub fn create_response(
     successes: &HashSet<(Reminder, ReminderScope)>,
     errors: &HashSet<ReminderError>,
     time: i64,
@@ -815,6 +1472,23 @@ fn create_response(
     embed
 }
 
+/// Normalize a list of role and user mentions, or `None` if there are none.
+fn parse_role_user_list(mentions: &str) -> Option<String> {
+    let list = REGEX_USER_ROLE
+        .captures_iter(mentions)
+        .map(|i| match i.get(1).map(|m| m.as_str()) {
+            Some("&") => format!("<@&{}>", &i[2]),
+            _ => format!("<@{}>", &i[2]),
+        })
+        .collect::<Vec<String>>();
+
+    if list.is_empty() {
+        None
+    } else {
+        Some(list.join(" "))
+    }
+}
+
 fn parse_mention_list(mentions: &str) -> Vec<ReminderScope> {
     REGEX_CHANNEL_USER
         .captures_iter(mentions)
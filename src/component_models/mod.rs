@@ -2,6 +2,7 @@ pub(crate) mod pager;
 
 use std::io::Cursor;
 
+use chrono::Utc;
 use chrono_tz::Tz;
 use log::warn;
 use poise::{
@@ -18,22 +19,33 @@ use poise::{
         Context,
     },
 };
+use postman::components::{AcknowledgeReminder, SnoozeReminder};
 use rmp_serde::Serializer;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     commands::{
         command_macro::list::{max_macro_page, show_macro_page},
-        reminder_cmds::{max_delete_page, show_delete_page},
+        reminder_cmds::{create_response, max_delete_page, show_delete_page},
         todo_cmds::{max_todo_page, show_todo_page},
     },
     component_models::pager::{DelPager, LookPager, MacroPager, Pager, TodoPager},
     consts::{EMBED_DESCRIPTION_MAX_LENGTH, THEME_COLOR},
-    models::reminder::Reminder,
+    models::{
+        reminder::{
+            builder::{MultiReminderBuilder, ReminderScope},
+            content::Content,
+            Reminder,
+        },
+        user_data::UserData,
+    },
     utils::send_as_initial_response,
     Data,
 };
 
+/// Title of the embed listing who has marked a reminder done
+const ACKNOWLEDGED_TITLE: &str = "Marked done by";
+
 #[derive(Deserialize, Serialize)]
 #[serde(tag = "type")]
 #[repr(u8)]
@@ -45,6 +57,8 @@ pub enum ComponentDataModel {
     TodoSelector(TodoSelector),
     MacroPager(MacroPager),
     UndoReminder(UndoReminder),
+    AcknowledgeReminder(AcknowledgeReminder),
+    SnoozeReminder(SnoozeReminder),
 }
 
 impl ComponentDataModel {
@@ -405,6 +419,181 @@ WHERE guilds.guild = ?",
                         .await;
                 }
             }
+            ComponentDataModel::AcknowledgeReminder(acknowledge) => {
+                let message = &component.message;
+
+                if let Err(e) = sqlx::query!(
+                    "
+INSERT IGNORE INTO reminder_acknowledgements (reminder_id, occurrence, channel, message_id, user)
+VALUES (?, ?, ?, ?, ?)
+                    ",
+                    acknowledge.reminder_id,
+                    acknowledge.occurrence,
+                    message.channel_id.0,
+                    message.id.0,
+                    component.user.id.0
+                )
+                .execute(&data.database)
+                .await
+                {
+                    warn!("Could not record acknowledgement: {:?}", e);
+
+                    let _ = component
+                        .create_interaction_response(&ctx, |f| {
+                            f.kind(InteractionResponseType::ChannelMessageWithSource)
+                                .interaction_response_data(|d| {
+                                    d.content("Your acknowledgement could not be recorded.")
+                                        .ephemeral(true)
+                                })
+                        })
+                        .await;
+
+                    return;
+                }
+
+                // nothing to escalate once someone has marked it done
+                let _ = sqlx::query!(
+                    "DELETE FROM pending_escalations WHERE message_id = ? AND occurrence = ?",
+                    message.id.0,
+                    acknowledge.occurrence
+                )
+                .execute(&data.database)
+                .await;
+
+                let users = sqlx::query!(
+                    "
+SELECT user FROM reminder_acknowledgements
+WHERE message_id = ? AND occurrence = ?
+ORDER BY acknowledged_at, id
+                    ",
+                    message.id.0,
+                    acknowledge.occurrence
+                )
+                .fetch_all(&data.database)
+                .await
+                .unwrap_or_default();
+
+                let mut description = String::new();
+                for (count, user) in users.iter().enumerate() {
+                    let line = format!("<@{}>\n", user.user);
+
+                    if description.len() + line.len() > EMBED_DESCRIPTION_MAX_LENGTH - 32 {
+                        description += &format!("and {} more", users.len() - count);
+                        break;
+                    }
+
+                    description += &line;
+                }
+
+                // keep the reminder's own embeds, replacing the list from any earlier click
+                let mut embeds = message
+                    .embeds
+                    .iter()
+                    .filter(|e| e.title.as_deref() != Some(ACKNOWLEDGED_TITLE))
+                    .cloned()
+                    .map(CreateEmbed::from)
+                    .collect::<Vec<CreateEmbed>>();
+
+                if embeds.len() < 10 {
+                    let mut embed = CreateEmbed::default();
+                    embed.title(ACKNOWLEDGED_TITLE).description(description).color(*THEME_COLOR);
+
+                    embeds.push(embed);
+                }
+
+                let _ = component
+                    .create_interaction_response(&ctx, |f| {
+                        f.kind(InteractionResponseType::UpdateMessage)
+                            .interaction_response_data(|d| d.set_embeds(embeds))
+                    })
+                    .await;
+            }
+            ComponentDataModel::SnoozeReminder(snooze) => {
+                let message = &component.message;
+
+                // copying the attachments can take longer than an interaction response may
+                let _ = component
+                    .create_interaction_response(&ctx, |f| {
+                        f.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                            .interaction_response_data(|d| d.ephemeral(true))
+                    })
+                    .await;
+
+                let user_data =
+                    match UserData::from_user(&component.user, &ctx, &data.database).await {
+                        Ok(user_data) => user_data,
+                        Err(e) => {
+                            warn!("Could not load user for snooze: {:?}", e);
+                            return;
+                        }
+                    };
+
+                let mut content = Content::new();
+                content.content = message.content.clone();
+
+                let mut attachments = vec![];
+                for attachment in &message.attachments {
+                    match attachment.download().await {
+                        Ok(bytes) => attachments.push((attachment.filename.clone(), bytes)),
+                        Err(e) => warn!("Could not download attachment for snooze: {:?}", e),
+                    }
+                }
+
+                // the first attachment is the reminder's own, the rest are sent after it
+                let mut attachments = attachments.into_iter();
+                if let Some((name, bytes)) = attachments.next() {
+                    content.attachment = Some(bytes);
+                    content.attachment_name = Some(name);
+                }
+                let attachments = attachments.collect::<Vec<_>>();
+
+                let time = Utc::now().timestamp() + snooze.minutes as i64 * 60;
+
+                let mut builder = MultiReminderBuilder::from_parts(ctx, data, None)
+                    .author(user_data)
+                    .content(content)
+                    .time(time)
+                    .snooze_buttons(true);
+
+                builder.set_scopes(vec![ReminderScope::User(component.user.id.0)]);
+
+                let (errors, successes) = builder.build().await;
+
+                // the first embed that isn't a list of clicks is the reminder's own, the rest are
+                // sent after it
+                let mut embeds = message
+                    .embeds
+                    .iter()
+                    .filter(|e| e.title.as_deref() != Some(ACKNOWLEDGED_TITLE));
+                let embed = embeds.next();
+                let embeds = embeds.collect::<Vec<_>>();
+
+                for (reminder, _) in &successes {
+                    if let Some(embed) = embed {
+                        if let Err(e) = reminder.set_embed(&data.database, embed).await {
+                            warn!("Could not copy embed to snoozed reminder: {:?}", e);
+                        }
+                    }
+
+                    for embed in &embeds {
+                        if let Err(e) = reminder.add_embed(&data.database, embed).await {
+                            warn!("Could not copy embed to snoozed reminder: {:?}", e);
+                        }
+                    }
+
+                    for (name, bytes) in &attachments {
+                        if let Err(e) = reminder.add_attachment(&data.database, name, bytes).await {
+                            warn!("Could not copy attachment to snoozed reminder: {:?}", e);
+                        }
+                    }
+                }
+
+                let embed = create_response(&successes, &errors, time);
+
+                let _ = component
+                    .edit_original_interaction_response(&ctx, |r| r.set_embed(embed))
+                    .await;
+            }
         }
     }
 }
@@ -428,3 +617,39 @@ pub struct UndoReminder {
     pub user_id: serenity::UserId,
     pub reminder_id: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // postman's `components` tests encode the same custom IDs
+    #[test]
+    fn decodes_postman_custom_ids() {
+        let acknowledge = "k7NBY2tub3dsZWRnZVJlbWluZGVyDAM=".to_string();
+        match ComponentDataModel::from_custom_id(&acknowledge) {
+            ComponentDataModel::AcknowledgeReminder(AcknowledgeReminder {
+                reminder_id: 12,
+                occurrence: 3,
+            }) => {}
+            _ => panic!("decoded the wrong component"),
+        }
+        assert_eq!(
+            ComponentDataModel::AcknowledgeReminder(AcknowledgeReminder {
+                reminder_id: 12,
+                occurrence: 3
+            })
+            .to_custom_id(),
+            acknowledge
+        );
+
+        let snooze = "kq5Tbm9vemVSZW1pbmRlcgo=".to_string();
+        match ComponentDataModel::from_custom_id(&snooze) {
+            ComponentDataModel::SnoozeReminder(SnoozeReminder { minutes: 10 }) => {}
+            _ => panic!("decoded the wrong component"),
+        }
+        assert_eq!(
+            ComponentDataModel::SnoozeReminder(SnoozeReminder { minutes: 10 }).to_custom_id(),
+            snooze
+        );
+    }
+}
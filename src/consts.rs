@@ -26,6 +26,7 @@ lazy_static! {
     )
         .into();
     pub static ref REGEX_CHANNEL_USER: Regex = Regex::new(r#"\s*<(#|@)(?:!)?(\d+)>\s*"#).unwrap();
+    pub static ref REGEX_USER_ROLE: Regex = Regex::new(r#"<@(!|&)?(\d+)>"#).unwrap();
     pub static ref SUBSCRIPTION_ROLES: HashSet<u64> = HashSet::from_iter(
         env::var("SUBSCRIPTION_ROLES")
             .map(|var| var
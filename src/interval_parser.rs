@@ -111,12 +111,15 @@ impl OverflowOp for u64 {
 pub struct Interval {
     pub month: u64,
     pub sec: u64,
+    /// Occurrences falling on a weekend or guild holiday move to the next business day
+    pub weekdays: bool,
 }
 
 struct Parser<'a> {
     iter: Chars<'a>,
     src: &'a str,
     current: (u64, u64, u64),
+    weekdays: bool,
 }
 
 impl<'a> Parser<'a> {
@@ -149,6 +152,11 @@ impl<'a> Parser<'a> {
             "hours" | "hour" | "hr" | "hrs" | "h" => (0, n.mul(3600)?, 0),
             "days" | "day" | "d" => (0, n.mul(86400)?, 0),
             "weeks" | "week" | "w" => (0, n.mul(86400 * 7)?, 0),
+            "weekdays" | "weekday" | "businessdays" | "businessday" | "wd" => {
+                self.weekdays = true;
+
+                (0, n.mul(86400)?, 0)
+            }
             "months" | "month" | "M" => (n, 0, 0),
             "years" | "year" | "y" => (12, 0, 0),
             _ => {
@@ -215,7 +223,13 @@ impl<'a> Parser<'a> {
             self.parse_unit(n, start, off)?;
             n = match self.parse_first_char()? {
                 Some(n) => n,
-                None => return Ok(Interval { month: self.current.0, sec: self.current.1 }),
+                None => {
+                    return Ok(Interval {
+                        month: self.current.0,
+                        sec: self.current.1,
+                        weekdays: self.weekdays,
+                    })
+                }
             };
         }
     }
@@ -234,6 +248,8 @@ impl<'a> Parser<'a> {
 /// * `hours`, `hour`, `hr`, `h`
 /// * `days`, `day`, `d`
 /// * `weeks`, `week`, `w`
+/// * `weekdays`, `weekday`, `businessdays`, `businessday`, `wd` -- days, moved past weekends
+///   and guild holidays
 /// * `months`, `month`, `M` -- defined as 30.44 days
 /// * `years`, `year`, `y` -- defined as 365.25 days
 ///
@@ -247,5 +263,5 @@ impl<'a> Parser<'a> {
 /// assert_eq!(parse_duration("32ms"), Ok(Duration::new(0, 32_000_000)));
 /// ```
 pub fn parse_duration(s: &str) -> Result<Interval, Error> {
-    Parser { iter: s.chars(), src: s, current: (0, 0, 0) }.parse()
+    Parser { iter: s.chars(), src: s, current: (0, 0, 0), weekdays: false }.parse()
 }
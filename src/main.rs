@@ -20,6 +20,7 @@ use std::{
     fmt::{Debug, Display, Formatter},
 };
 
+use chrono::NaiveDateTime;
 use chrono_tz::Tz;
 use dotenv::dotenv;
 use log::{error, warn};
@@ -28,7 +29,12 @@ use poise::serenity_prelude::model::{
     id::{GuildId, UserId},
 };
 use sqlx::{MySql, Pool};
-use tokio::sync::{broadcast, broadcast::Sender, RwLock};
+use tokio::sync::{
+    broadcast,
+    broadcast::Sender,
+    mpsc::{unbounded_channel, UnboundedSender},
+    RwLock,
+};
 
 use crate::{
     commands::{command_macro, info_cmds, moderation_cmds, reminder_cmds, todo_cmds},
@@ -50,6 +56,7 @@ pub struct Data {
     http: reqwest::Client,
     recording_macros: RwLock<HashMap<(GuildId, UserId), CommandMacro<Data, Error>>>,
     popular_timezones: Vec<Tz>,
+    wakeup: UnboundedSender<NaiveDateTime>,
     _broadcast: Sender<()>,
 }
 
@@ -109,6 +116,8 @@ async fn _main(tx: Sender<()>) -> Result<(), Box<dyn StdError + Send + Sync>> {
                 ..moderation_cmds::allowed_dm()
             },
             moderation_cmds::webhook(),
+            moderation_cmds::log_channel(),
+            moderation_cmds::missed_policy(),
             poise::Command {
                 subcommands: vec![
                     command_macro::delete::delete_macro(),
@@ -123,7 +132,10 @@ async fn _main(tx: Sender<()>) -> Result<(), Box<dyn StdError + Send + Sync>> {
             reminder_cmds::pause(),
             reminder_cmds::offset(),
             reminder_cmds::nudge(),
+            reminder_cmds::digest(),
+            reminder_cmds::skip(),
             reminder_cmds::look(),
+            reminder_cmds::history(),
             reminder_cmds::delete(),
             poise::Command {
                 subcommands: vec![
@@ -198,11 +210,14 @@ async fn _main(tx: Sender<()>) -> Result<(), Box<dyn StdError + Send + Sync>> {
                 let pool1 = database.clone();
                 let pool2 = database.clone();
 
+                let (wakeup, wakeup_recv) = unbounded_channel();
+                let wakeup2 = wakeup.clone();
+
                 let run_settings = env::var("DONTRUN").unwrap_or_else(|_| "".to_string());
 
                 if !run_settings.contains("postman") {
                     tokio::spawn(async move {
-                        match postman::initialize(kill_recv, ctx1, &pool1).await {
+                        match postman::initialize(kill_recv, ctx1, pool1, wakeup_recv).await {
                             Ok(_) => {}
                             Err(e) => {
                                 error!("postman exiting: {}", e);
@@ -215,7 +230,7 @@ async fn _main(tx: Sender<()>) -> Result<(), Box<dyn StdError + Send + Sync>> {
 
                 if !run_settings.contains("web") {
                     tokio::spawn(async move {
-                        reminder_web::initialize(kill_tx, ctx2, pool2).await.unwrap();
+                        reminder_web::initialize(kill_tx, ctx2, pool2, wakeup2).await.unwrap();
                     });
                 } else {
                     warn!("Not running web");
@@ -226,6 +241,7 @@ async fn _main(tx: Sender<()>) -> Result<(), Box<dyn StdError + Send + Sync>> {
                     database,
                     popular_timezones,
                     recording_macros: Default::default(),
+                    wakeup,
                     _broadcast: tx,
                 })
             })
@@ -11,6 +11,7 @@ pub struct ChannelData {
     pub webhook_token: Option<String>,
     pub paused: bool,
     pub paused_until: Option<NaiveDateTime>,
+    pub digest: bool,
 }
 
 impl ChannelData {
@@ -23,7 +24,7 @@ impl ChannelData {
         if let Ok(c) = sqlx::query_as_unchecked!(
             Self,
             "
-SELECT id, name, nudge, blacklisted, webhook_id, webhook_token, paused, paused_until FROM channels WHERE channel = ?
+SELECT id, name, nudge, blacklisted, webhook_id, webhook_token, paused, paused_until, digest FROM channels WHERE channel = ?
             ",
             channel_id
         )
@@ -50,7 +51,7 @@ INSERT IGNORE INTO channels (channel, name, guild_id) VALUES (?, ?, (SELECT id F
             Ok(sqlx::query_as_unchecked!(
                 Self,
                 "
-SELECT id, name, nudge, blacklisted, webhook_id, webhook_token, paused, paused_until FROM channels WHERE channel = ?
+SELECT id, name, nudge, blacklisted, webhook_id, webhook_token, paused, paused_until, digest FROM channels WHERE channel = ?
                 ",
                 channel_id
             )
@@ -63,7 +64,7 @@ SELECT id, name, nudge, blacklisted, webhook_id, webhook_token, paused, paused_u
         sqlx::query!(
             "
 UPDATE channels SET name = ?, nudge = ?, blacklisted = ?, webhook_id = ?, webhook_token = ?, paused = ?, paused_until \
-             = ? WHERE id = ?
+             = ?, digest = ? WHERE id = ?
             ",
             self.name,
             self.nudge,
@@ -72,6 +73,7 @@ UPDATE channels SET name = ?, nudge = ?, blacklisted = ?, webhook_id = ?, webhoo
             self.webhook_token,
             self.paused,
             self.paused_until,
+            self.digest,
             self.id
         )
         .execute(pool)
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use poise::serenity_prelude::model::id::{ChannelId, GuildId};
+use sqlx::{Executor, MySql};
+
+pub struct Delivery {
+    pub channel: u64,
+    pub thread_id: Option<u64>,
+    pub message_id: Option<u64>,
+    pub method: Option<String>,
+    pub sent_at: DateTime<Utc>,
+    pub error: Option<String>,
+    pub content: String,
+}
+
+impl Delivery {
+    pub async fn from_channel<C: Into<ChannelId>>(
+        pool: impl Executor<'_, Database = MySql>,
+        channel_id: C,
+        limit: u32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let channel_id = channel_id.into();
+
+        sqlx::query_as_unchecked!(
+            Self,
+            "
+SELECT
+    channel,
+    thread_id,
+    message_id,
+    method,
+    sent_at,
+    error,
+    content
+FROM
+    reminder_deliveries
+WHERE
+    channel = ? OR thread_id = ?
+ORDER BY
+    sent_at DESC, id DESC
+LIMIT ?
+            ",
+            channel_id.as_u64(),
+            channel_id.as_u64(),
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub fn display(&self, guild_id: Option<GuildId>, timezone: &Tz) -> String {
+        let time = self.sent_at.with_timezone(timezone).format("%Y-%m-%d %H:%M:%S");
+
+        match (&self.error, self.message_id) {
+            (Some(error), _) => format!("**{}**: '{}' *not sent*: {}\n", time, self.content, error),
+
+            (None, Some(message_id)) => format!(
+                "**{}**: '{}' *sent via {}*: https://discord.com/channels/{}/{}/{}\n",
+                time,
+                self.content,
+                self.method.as_deref().unwrap_or("unknown"),
+                guild_id.map_or("@me".to_string(), |g| g.to_string()),
+                self.thread_id.unwrap_or(self.channel),
+                message_id
+            ),
+
+            (None, None) => format!(
+                "**{}**: '{}' *sent via {}*\n",
+                time,
+                self.content,
+                self.method.as_deref().unwrap_or("unknown")
+            ),
+        }
+    }
+}
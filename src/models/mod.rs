@@ -1,5 +1,6 @@
 pub mod channel_data;
 pub mod command_macro;
+pub mod delivery;
 pub mod reminder;
 pub mod timer;
 pub mod user_data;
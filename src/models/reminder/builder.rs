@@ -1,17 +1,19 @@
 use std::{collections::HashSet, fmt::Display};
 
-use chrono::{Duration, NaiveDateTime, Utc};
+use chrono::{Datelike, Duration, NaiveDateTime, TimeZone, Utc};
 use chrono_tz::Tz;
 use poise::serenity_prelude::{
     http::CacheHttp,
     model::{
-        channel::GuildChannel,
+        channel::{Channel, ChannelType, GuildChannel},
         id::{ChannelId, GuildId, UserId},
         webhook::Webhook,
     },
-    Result as SerenityResult,
+    Context as SerenityContext, Result as SerenityResult,
 };
+use postman::schedule::{parse_cron, shortest_interval};
 use sqlx::MySqlPool;
+use tokio::sync::mpsc::UnboundedSender;
 
 use crate::{
     consts::{DAY, DEFAULT_AVATAR, MAX_TIME, MIN_INTERVAL},
@@ -21,7 +23,7 @@ use crate::{
         reminder::{content::Content, errors::ReminderError, helper::generate_uid, Reminder},
         user_data::UserData,
     },
-    Context,
+    Context, Data,
 };
 
 async fn create_webhook(
@@ -32,6 +34,23 @@ async fn create_webhook(
     channel.create_webhook_with_avatar(ctx.http(), name, DEFAULT_AVATAR.clone()).await
 }
 
+/// The channel a reminder set in `channel` is stored against, and the thread it posts in, if any.
+/// Threads can't have webhooks, so reminders in a thread are stored against the thread's parent.
+async fn resolve_thread(
+    ctx: impl CacheHttp,
+    channel: GuildChannel,
+) -> Option<(GuildChannel, Option<u64>)> {
+    match channel.kind {
+        ChannelType::PublicThread | ChannelType::PrivateThread | ChannelType::NewsThread => {
+            let parent = channel.parent_id?.to_channel(&ctx).await.ok()?.guild()?;
+
+            Some((parent, Some(channel.id.0)))
+        }
+
+        _ => Some((channel, None)),
+    }
+}
+
 #[derive(Hash, PartialEq, Eq)]
 pub enum ReminderScope {
     User(u64),
@@ -49,13 +68,32 @@ impl ReminderScope {
 
 pub struct ReminderBuilder {
     pool: MySqlPool,
+    wakeup: UnboundedSender<NaiveDateTime>,
     uid: String,
     channel: u32,
     utc_time: NaiveDateTime,
     timezone: String,
     interval_secs: Option<i64>,
     interval_months: Option<i64>,
+    interval_weekdays: bool,
+    interval_wallclock: bool,
+    month_anchor: Option<String>,
+    month_anchor_day: Option<u32>,
+    cron: Option<String>,
+    rrule: Option<String>,
     expires: Option<NaiveDateTime>,
+    max_occurrences: Option<u32>,
+    replace_mode: Option<String>,
+    delete_after: Option<u32>,
+    thread_id: Option<u64>,
+    thread_title: Option<String>,
+    ack_button: bool,
+    snooze_buttons: bool,
+    escalate_after: Option<u32>,
+    escalate_mentions: Option<String>,
+    escalate_user: Option<u64>,
+    escalate_limit: u32,
+    allowed_mentions: Option<String>,
     content: String,
     tts: bool,
     attachment_name: Option<String>,
@@ -88,13 +126,48 @@ INSERT INTO reminders (
     `timezone`,
     `interval_seconds`,
     `interval_months`,
+    `interval_weekdays`,
+    `interval_wallclock`,
+    `month_anchor`,
+    `month_anchor_day`,
+    `cron`,
+    `rrule`,
     `expires`,
+    `max_occurrences`,
+    `replace_mode`,
+    `delete_after`,
+    `thread_id`,
+    `thread_title`,
+    `ack_button`,
+    `snooze_buttons`,
+    `escalate_after`,
+    `escalate_mentions`,
+    `escalate_user`,
+    `escalate_limit`,
+    `allowed_mentions`,
     `content`,
     `tts`,
     `attachment_name`,
     `attachment`,
     `set_by`
 ) VALUES (
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
+    ?,
     ?,
     ?,
     ?,
@@ -115,7 +188,25 @@ INSERT INTO reminders (
                         self.timezone,
                         self.interval_secs,
                         self.interval_months,
+                        self.interval_weekdays,
+                        self.interval_wallclock,
+                        self.month_anchor,
+                        self.month_anchor_day,
+                        self.cron,
+                        self.rrule,
                         self.expires,
+                        self.max_occurrences,
+                        self.replace_mode,
+                        self.delete_after,
+                        self.thread_id,
+                        self.thread_title,
+                        self.ack_button,
+                        self.snooze_buttons,
+                        self.escalate_after,
+                        self.escalate_mentions,
+                        self.escalate_user,
+                        self.escalate_limit,
+                        self.allowed_mentions,
                         self.content,
                         self.tts,
                         self.attachment_name,
@@ -126,6 +217,8 @@ INSERT INTO reminders (
                     .await
                     .unwrap();
 
+                    let _ = self.wakeup.send(utc_time);
+
                     Ok(Reminder::from_uid(&self.pool, &self.uid).await.unwrap())
                 }
             }
@@ -140,24 +233,61 @@ pub struct MultiReminderBuilder<'a> {
     utc_time: NaiveDateTime,
     timezone: Tz,
     interval: Option<Interval>,
+    interval_wallclock: bool,
+    month_anchor: Option<String>,
+    cron: Option<String>,
+    rrule: Option<String>,
     expires: Option<NaiveDateTime>,
+    max_occurrences: Option<u32>,
+    replace_mode: Option<String>,
+    delete_after: Option<u32>,
+    thread_title: Option<String>,
+    ack_button: bool,
+    snooze_buttons: bool,
+    escalate_after: Option<u32>,
+    escalate_mentions: Option<String>,
+    escalate_user: Option<u64>,
+    escalate_limit: u32,
+    allowed_mentions: Option<String>,
     content: Content,
     set_by: Option<u32>,
-    ctx: &'a Context<'a>,
+    ctx: &'a SerenityContext,
+    data: &'a Data,
     guild_id: Option<GuildId>,
 }
 
 impl<'a> MultiReminderBuilder<'a> {
-    pub fn new(ctx: &'a Context, guild_id: Option<GuildId>) -> Self {
+    pub fn new(ctx: &Context<'a>, guild_id: Option<GuildId>) -> Self {
+        Self::from_parts(ctx.discord(), ctx.data(), guild_id)
+    }
+
+    /// Create a builder outside of a command, such as from a component interaction.
+    pub fn from_parts(ctx: &'a SerenityContext, data: &'a Data, guild_id: Option<GuildId>) -> Self {
         MultiReminderBuilder {
             scopes: vec![],
             utc_time: Utc::now().naive_utc(),
             timezone: Tz::UTC,
             interval: None,
+            interval_wallclock: false,
+            month_anchor: None,
+            cron: None,
+            rrule: None,
             expires: None,
+            max_occurrences: None,
+            replace_mode: None,
+            delete_after: None,
+            thread_title: None,
+            ack_button: false,
+            snooze_buttons: false,
+            escalate_after: None,
+            escalate_mentions: None,
+            escalate_user: None,
+            escalate_limit: 1,
+            allowed_mentions: None,
             content: Content::new(),
             set_by: None,
             ctx,
+            data,
             guild_id,
         }
     }
@@ -201,6 +331,97 @@ impl<'a> MultiReminderBuilder<'a> {
         self
     }
 
+    pub fn max_occurrences(mut self, max_occurrences: Option<u32>) -> Self {
+        self.max_occurrences = max_occurrences;
+
+        self
+    }
+
+    pub fn interval_wallclock(mut self, interval_wallclock: bool) -> Self {
+        self.interval_wallclock = interval_wallclock;
+
+        self
+    }
+
+    pub fn replace_mode(mut self, replace_mode: Option<String>) -> Self {
+        self.replace_mode = replace_mode;
+
+        self
+    }
+
+    pub fn delete_after(mut self, delete_after: Option<u32>) -> Self {
+        self.delete_after = delete_after;
+
+        self
+    }
+
+    pub fn thread_title(mut self, thread_title: Option<String>) -> Self {
+        self.thread_title = thread_title;
+
+        self
+    }
+
+    pub fn ack_button(mut self, ack_button: bool) -> Self {
+        self.ack_button = ack_button;
+
+        self
+    }
+
+    pub fn snooze_buttons(mut self, snooze_buttons: bool) -> Self {
+        self.snooze_buttons = snooze_buttons;
+
+        self
+    }
+
+    pub fn escalate_after(mut self, escalate_after: Option<u32>) -> Self {
+        self.escalate_after = escalate_after;
+
+        self
+    }
+
+    pub fn escalate_mentions(mut self, escalate_mentions: Option<String>) -> Self {
+        self.escalate_mentions = escalate_mentions;
+
+        self
+    }
+
+    pub fn escalate_user(mut self, escalate_user: Option<u64>) -> Self {
+        self.escalate_user = escalate_user;
+
+        self
+    }
+
+    pub fn escalate_limit(mut self, escalate_limit: u32) -> Self {
+        self.escalate_limit = escalate_limit;
+
+        self
+    }
+
+    /// Discord's allowed mentions object, as JSON
+    pub fn allowed_mentions(mut self, allowed_mentions: Option<String>) -> Self {
+        self.allowed_mentions = allowed_mentions;
+
+        self
+    }
+
+    pub fn month_anchor(mut self, month_anchor: Option<String>) -> Self {
+        self.month_anchor = month_anchor;
+
+        self
+    }
+
+    pub fn cron(mut self, cron: Option<String>) -> Self {
+        self.cron = cron;
+
+        self
+    }
+
+    pub fn rrule(mut self, rrule: Option<String>) -> Self {
+        self.rrule = rrule;
+
+        self
+    }
+
     pub fn set_scopes(&mut self, scopes: Vec<ReminderScope>) {
         self.scopes = scopes;
     }
@@ -215,75 +436,84 @@ impl<'a> MultiReminderBuilder<'a> {
         } else if self.interval.map_or(false, |i| ((i.sec + i.month * 30 * DAY) as i64) > *MAX_TIME)
         {
             errors.insert(ReminderError::LongInterval);
+        } else if self.cron.as_ref().map_or(false, |c| {
+            parse_cron(c)
+                .ok()
+                .and_then(|schedule| shortest_interval(&schedule, self.timezone))
+                .map_or(true, |i| i < *MIN_INTERVAL)
+        }) {
+            errors.insert(ReminderError::ShortInterval);
         } else {
             for scope in self.scopes {
                 let db_channel_id = match scope {
                     ReminderScope::User(user_id) => {
-                        if let Ok(user) = UserId(user_id).to_user(&self.ctx.discord()).await {
-                            let user_data = UserData::from_user(
-                                &user,
-                                &self.ctx.discord(),
-                                &self.ctx.data().database,
-                            )
-                            .await
-                            .unwrap();
+                        if let Ok(user) = UserId(user_id).to_user(&self.ctx).await {
+                            let user_data =
+                                UserData::from_user(&user, &self.ctx, &self.data.database)
+                                    .await
+                                    .unwrap();
 
                             if let Some(guild_id) = self.guild_id {
-                                if guild_id.member(&self.ctx.discord(), user).await.is_err() {
+                                if guild_id.member(&self.ctx, user).await.is_err() {
                                     Err(ReminderError::InvalidTag)
                                 } else if self.set_by.map_or(true, |i| i != user_data.id)
                                     && !user_data.allowed_dm
                                 {
                                     Err(ReminderError::UserBlockedDm)
                                 } else {
-                                    Ok(user_data.dm_channel)
+                                    Ok((user_data.dm_channel, None))
                                 }
                             } else {
-                                Ok(user_data.dm_channel)
+                                Ok((user_data.dm_channel, None))
                             }
                         } else {
                             Err(ReminderError::InvalidTag)
                         }
                     }
                     ReminderScope::Channel(channel_id) => {
-                        let channel =
-                            ChannelId(channel_id).to_channel(&self.ctx.discord()).await.unwrap();
+                        let channel = ChannelId(channel_id).to_channel(&self.ctx).await.unwrap();
 
-                        if let Some(guild_channel) = channel.clone().guild() {
+                        let target = match channel.guild() {
+                            Some(guild_channel) => resolve_thread(&self.ctx, guild_channel).await,
+                            None => None,
+                        };
+
+                        if let Some((guild_channel, thread_id)) = target {
                             if Some(guild_channel.guild_id) != self.guild_id {
                                 Err(ReminderError::InvalidTag)
+                            } else if thread_id.is_some() && self.thread_title.is_some() {
+                                Err(ReminderError::ThreadInThread)
+                            } else if guild_channel.kind == ChannelType::Forum
+                                && self.thread_title.is_none()
+                            {
+                                Err(ReminderError::ForumThreadTitle)
                             } else {
-                                let mut channel_data =
-                                    ChannelData::from_channel(&channel, &self.ctx.data().database)
-                                        .await
-                                        .unwrap();
+                                let mut channel_data = ChannelData::from_channel(
+                                    &Channel::Guild(guild_channel.clone()),
+                                    &self.data.database,
+                                )
+                                .await
+                                .unwrap();
 
                                 if channel_data.webhook_id.is_none()
                                     || channel_data.webhook_token.is_none()
                                 {
-                                    match create_webhook(
-                                        &self.ctx.discord(),
-                                        guild_channel,
-                                        "Reminder",
-                                    )
-                                    .await
+                                    match create_webhook(&self.ctx, guild_channel, "Reminder").await
                                     {
                                         Ok(webhook) => {
                                             channel_data.webhook_id =
                                                 Some(webhook.id.as_u64().to_owned());
                                             channel_data.webhook_token = webhook.token;
 
-                                            channel_data
-                                                .commit_changes(&self.ctx.data().database)
-                                                .await;
+                                            channel_data.commit_changes(&self.data.database).await;
 
-                                            Ok(channel_data.id)
+                                            Ok((channel_data.id, thread_id))
                                         }
 
                                         Err(e) => Err(ReminderError::DiscordError(e.to_string())),
                                     }
                                 } else {
-                                    Ok(channel_data.id)
+                                    Ok((channel_data.id, thread_id))
                                 }
                             }
                         } else {
@@ -293,16 +523,38 @@ impl<'a> MultiReminderBuilder<'a> {
                 };
 
                 match db_channel_id {
-                    Ok(c) => {
+                    Ok((c, thread_id)) => {
                         let builder = ReminderBuilder {
-                            pool: self.ctx.data().database.clone(),
+                            pool: self.data.database.clone(),
+                            wakeup: self.data.wakeup.clone(),
                             uid: generate_uid(),
                             channel: c,
                             utc_time: self.utc_time,
                             timezone: self.timezone.to_string(),
                             interval_secs: self.interval.map(|i| i.sec as i64),
                             interval_months: self.interval.map(|i| i.month as i64),
+                            interval_weekdays: self.interval.map_or(false, |i| i.weekdays),
+                            interval_wallclock: self.interval_wallclock,
+                            month_anchor: self.month_anchor.clone(),
+                            month_anchor_day: self
+                                .month_anchor
+                                .as_ref()
+                                .map(|_| self.timezone.from_utc_datetime(&self.utc_time).day()),
+                            cron: self.cron.clone(),
+                            rrule: self.rrule.clone(),
                             expires: self.expires,
+                            max_occurrences: self.max_occurrences,
+                            replace_mode: self.replace_mode.clone(),
+                            delete_after: self.delete_after,
+                            thread_id,
+                            thread_title: self.thread_title.clone(),
+                            ack_button: self.ack_button,
+                            snooze_buttons: self.snooze_buttons,
+                            escalate_after: self.escalate_after,
+                            escalate_mentions: self.escalate_mentions.clone(),
+                            escalate_user: self.escalate_user,
+                            escalate_limit: self.escalate_limit,
+                            allowed_mentions: self.allowed_mentions.clone(),
                             content: self.content.content.clone(),
                             tts: self.content.tts,
                             attachment_name: self.content.attachment_name.clone(),
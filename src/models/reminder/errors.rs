@@ -8,6 +8,8 @@ pub enum ReminderError {
     ShortInterval,
     InvalidTag,
     UserBlockedDm,
+    ForumThreadTitle,
+    ThreadInThread,
     DiscordError(String),
 }
 
@@ -34,6 +36,12 @@ impl ToString for ReminderError {
             ReminderError::UserBlockedDm => {
                 "User has DM reminders disabled".to_string()
             }
+            ReminderError::ForumThreadTitle => {
+                "Reminders in a forum channel need a `thread_title` for their posts".to_string()
+            }
+            ReminderError::ThreadInThread => {
+                "Reminders in a thread can't open new threads. Remove the `thread_title` or choose the parent channel".to_string()
+            }
             ReminderError::DiscordError(s) => format!("A Discord error occurred: **{}**", s),
         }
     }
@@ -9,9 +9,13 @@ use std::hash::{Hash, Hasher};
 use chrono::{DateTime, NaiveDateTime, Utc};
 use chrono_tz::Tz;
 use poise::serenity_prelude::{
-    model::id::{ChannelId, GuildId, UserId},
+    model::{
+        channel::Embed,
+        id::{ChannelId, GuildId, UserId},
+    },
     Cache,
 };
+use serde_json::json;
 use sqlx::Executor;
 
 use crate::{
@@ -296,6 +300,101 @@ WHERE
         sqlx::query!("DELETE FROM reminders WHERE uid = ?", self.uid).execute(db).await.map(|_| ())
     }
 
+    /// Copy a sent embed onto this reminder, such as when a delivered reminder is snoozed.
+    pub async fn set_embed(
+        &self,
+        db: impl Executor<'_, Database = Database>,
+        embed: &Embed,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "
+UPDATE reminders SET
+    embed_title = ?,
+    embed_description = ?,
+    embed_author = ?,
+    embed_author_url = ?,
+    embed_footer = ?,
+    embed_footer_url = ?,
+    embed_image_url = ?,
+    embed_thumbnail_url = ?,
+    embed_color = ?,
+    embed_fields = ?
+WHERE uid = ?
+            ",
+            embed.title.clone().unwrap_or_default(),
+            embed.description.clone().unwrap_or_default(),
+            embed.author.as_ref().map_or(String::new(), |a| a.name.clone()),
+            embed.author.as_ref().and_then(|a| a.icon_url.clone()),
+            embed.footer.as_ref().map_or(String::new(), |f| f.text.clone()),
+            embed.footer.as_ref().and_then(|f| f.icon_url.clone()),
+            embed.image.as_ref().map(|i| i.url.clone()),
+            embed.thumbnail.as_ref().map(|t| t.url.clone()),
+            embed.colour.map_or(0, |c| c.0),
+            embed_fields(embed),
+            self.uid
+        )
+        .execute(db)
+        .await
+        .map(|_| ())
+    }
+
+    /// Copy a sent embed into `reminder_embeds`, to be sent after the reminder's own.
+    pub async fn add_embed(
+        &self,
+        db: impl Executor<'_, Database = Database>,
+        embed: &Embed,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "
+INSERT INTO reminder_embeds (
+    reminder_id,
+    title,
+    description,
+    author,
+    author_url,
+    footer,
+    footer_url,
+    image_url,
+    thumbnail_url,
+    color,
+    fields
+) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ",
+            self.id,
+            embed.title.clone().unwrap_or_default(),
+            embed.description.clone().unwrap_or_default(),
+            embed.author.as_ref().map_or(String::new(), |a| a.name.clone()),
+            embed.author.as_ref().and_then(|a| a.icon_url.clone()),
+            embed.footer.as_ref().map_or(String::new(), |f| f.text.clone()),
+            embed.footer.as_ref().and_then(|f| f.icon_url.clone()),
+            embed.image.as_ref().map(|i| i.url.clone()),
+            embed.thumbnail.as_ref().map(|t| t.url.clone()),
+            embed.colour.map_or(0, |c| c.0),
+            embed_fields(embed)
+        )
+        .execute(db)
+        .await
+        .map(|_| ())
+    }
+
+    /// Add an attachment to `reminder_attachments`, to be sent after the reminder's own.
+    pub async fn add_attachment(
+        &self,
+        db: impl Executor<'_, Database = Database>,
+        name: &str,
+        data: &[u8],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "INSERT INTO reminder_attachments (reminder_id, name, data) VALUES (?, ?, ?)",
+            self.id,
+            name,
+            data
+        )
+        .execute(db)
+        .await
+        .map(|_| ())
+    }
+
     pub fn display_content(&self) -> &str {
         if self.content.is_empty() {
             &self.embed_description
@@ -340,3 +439,14 @@ WHERE
         }
     }
 }
+
+/// An embed's fields in the form stored in `embed_fields` and `reminder_embeds`.
+fn embed_fields(embed: &Embed) -> String {
+    let fields = embed
+        .fields
+        .iter()
+        .map(|f| json!({"title": f.name, "value": f.value, "inline": f.inline}))
+        .collect::<Vec<_>>();
+
+    json!(fields).to_string()
+}
@@ -12,11 +12,26 @@ pub const MAX_USERNAME_LENGTH: usize = 100;
 pub const MAX_EMBED_FIELDS: usize = 25;
 pub const MAX_EMBED_FIELD_TITLE_LENGTH: usize = 256;
 pub const MAX_EMBED_FIELD_VALUE_LENGTH: usize = 1024;
+pub const MAX_HOLIDAY_NAME_LENGTH: usize = 100;
+pub const MAX_THREAD_TITLE_LENGTH: usize = 100;
+pub const MAX_ESCALATE_MENTIONS_LENGTH: usize = 1000;
+pub const MAX_ESCALATIONS: u32 = 10;
+pub const MAX_ALLOWED_MENTIONS: usize = 100;
+pub const MAX_CONTENT_POOL: usize = 100;
+// including the attachment and embed stored on the reminder itself
+pub const MAX_ATTACHMENTS: usize = 10;
+pub const MAX_EMBEDS: usize = 10;
+pub const MAX_ATTACHMENT_NAME_LENGTH: usize = 260;
 
 pub const MINUTE: usize = 60;
 pub const HOUR: usize = 60 * MINUTE;
 pub const DAY: usize = 24 * HOUR;
 
+pub const MISSED_POLICIES: [&str; 4] = ["once", "all", "skip", "digest"];
+pub const MONTH_ANCHORS: [&str; 4] = ["clamp", "nth_weekday", "last_weekday", "last_day"];
+pub const REPLACE_MODES: [&str; 2] = ["edit", "repost"];
+pub const CONTENT_POOL_MODES: [&str; 2] = ["sequential", "random"];
+
 pub const CHARACTERS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_";
 
 use std::{collections::HashSet, env, iter::FromIterator};
@@ -8,11 +8,12 @@ mod routes;
 
 use std::{collections::HashMap, env};
 
+use chrono::NaiveDateTime;
 use oauth2::{basic::BasicClient, AuthUrl, ClientId, ClientSecret, RedirectUrl, TokenUrl};
 use rocket::{
     fs::FileServer,
     serde::json::{json, Value as JsonValue},
-    tokio::sync::broadcast::Sender,
+    tokio::sync::{broadcast::Sender, mpsc::UnboundedSender},
 };
 use rocket_dyn_templates::Template;
 use serenity::{
@@ -70,6 +71,7 @@ pub async fn initialize(
     kill_channel: Sender<()>,
     serenity_context: Context,
     db_pool: Pool<Database>,
+    wakeup: UnboundedSender<NaiveDateTime>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     info!("Checking environment variables...");
     env::var("OAUTH2_CLIENT_ID").expect("`OAUTH2_CLIENT_ID' not supplied");
@@ -105,6 +107,7 @@ pub async fn initialize(
         .manage(reqwest_client)
         .manage(serenity_context)
         .manage(db_pool)
+        .manage(wakeup)
         .mount("/static", FileServer::from(concat!(env!("CARGO_MANIFEST_DIR"), "/static")))
         .mount(
             "/",
@@ -146,8 +149,14 @@ pub async fn initialize(
                 routes::dashboard::guild::get_reminder_templates,
                 routes::dashboard::guild::create_reminder_template,
                 routes::dashboard::guild::delete_reminder_template,
+                routes::dashboard::guild::get_holidays,
+                routes::dashboard::guild::create_holiday,
+                routes::dashboard::guild::delete_holiday,
                 routes::dashboard::guild::create_guild_reminder,
                 routes::dashboard::guild::get_reminders,
+                routes::dashboard::guild::get_reminder_deliveries,
+                routes::dashboard::guild::get_reminder_attachment,
+                routes::dashboard::guild::skip_reminder,
                 routes::dashboard::guild::edit_reminder,
                 routes::dashboard::guild::delete_reminder,
                 routes::dashboard::export::export_reminders,
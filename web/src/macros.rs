@@ -46,6 +46,56 @@ macro_rules! check_url_opt {
     };
 }
 
+macro_rules! check_cron {
+    ($field:expr, $timezone:expr) => {
+        match postman::schedule::parse_cron($field) {
+            Ok(schedule) => {
+                if postman::schedule::shortest_interval(&schedule, $timezone)
+                    .map_or(true, |i| i < *crate::consts::MIN_INTERVAL as i64)
+                {
+                    return Err(json!({ "error": "Interval too short" }));
+                }
+            }
+
+            Err(e) => {
+                return Err(json!({ "error": format!("Cron expression invalid: {}", e) }));
+            }
+        }
+    };
+}
+
+macro_rules! check_rrule {
+    ($field:expr) => {
+        if let Err(e) = postman::schedule::validate_rrule($field) {
+            return Err(json!({ "error": format!("RRULE invalid: {}", e) }));
+        }
+    };
+}
+
+macro_rules! check_template {
+    ($field:expr) => {
+        if let Err(e) = postman::template::parse_template(&$field) {
+            return Err(json!({ "error": format!("Template invalid: {}", e) }));
+        }
+    };
+    ($field:expr, $($fields:expr),+) => {
+        check_template!($field);
+        check_template!($($fields),+);
+    };
+}
+
+macro_rules! check_template_opt {
+    ($field:expr) => {
+        if let Some(field) = &$field {
+            check_template!(field);
+        }
+    };
+    ($field:expr, $($fields:expr),+) => {
+        check_template_opt!($field);
+        check_template_opt!($($fields),+);
+    };
+}
+
 macro_rules! check_authorization {
     ($cookies:expr, $ctx:expr, $guild:expr) => {
         use serenity::model::id::UserId;
@@ -1,7 +1,9 @@
+use chrono::NaiveDateTime;
 use csv::{QuoteStyle, WriterBuilder};
 use rocket::{
     http::CookieJar,
     serde::json::{json, serde_json, Json},
+    tokio::sync::mpsc::UnboundedSender,
     State,
 };
 use serenity::{
@@ -40,11 +42,25 @@ pub async fn export_reminders(
             let result = sqlx::query_as_unchecked!(
                 ReminderCsv,
                 "SELECT
+                 reminders.ack_button,
+                 reminders.allowed_mentions,
                  reminders.attachment,
                  reminders.attachment_name,
+                 (
+                     SELECT JSON_ARRAYAGG(JSON_OBJECT(
+                         'name', reminder_attachments.name,
+                         'data', REPLACE(TO_BASE64(reminder_attachments.data), '\\n', '')
+                     ))
+                     FROM reminder_attachments WHERE reminder_attachments.reminder_id = reminders.id
+                 ) AS attachments,
                  reminders.avatar,
                  CONCAT('#', channels.channel) AS channel,
                  reminders.content,
+                 reminders.content_pool,
+                 reminders.content_pool_cursor,
+                 reminders.content_pool_mode,
+                 reminders.cron,
+                 reminders.delete_after,
                  reminders.embed_author,
                  reminders.embed_author_url,
                  reminders.embed_color,
@@ -55,12 +71,44 @@ pub async fn export_reminders(
                  reminders.embed_thumbnail_url,
                  reminders.embed_title,
                  reminders.embed_fields,
+                 (
+                     SELECT JSON_ARRAYAGG(JSON_OBJECT(
+                         'author', reminder_embeds.author,
+                         'author_url', reminder_embeds.author_url,
+                         'color', reminder_embeds.color,
+                         'description', reminder_embeds.description,
+                         'fields', IFNULL(reminder_embeds.fields, JSON_ARRAY()),
+                         'footer', reminder_embeds.footer,
+                         'footer_url', reminder_embeds.footer_url,
+                         'image_url', reminder_embeds.image_url,
+                         'thumbnail_url', reminder_embeds.thumbnail_url,
+                         'title', reminder_embeds.title
+                     ))
+                     FROM reminder_embeds WHERE reminder_embeds.reminder_id = reminders.id
+                 ) AS embeds,
                  reminders.enabled,
+                 reminders.escalate_after,
+                 reminders.escalate_limit,
+                 reminders.escalate_mentions,
+                 CAST(reminders.escalate_user AS CHAR) AS escalate_user,
+                 reminders.exclude_dates,
                  reminders.expires,
                  reminders.interval_seconds,
                  reminders.interval_months,
+                 reminders.interval_wallclock,
+                 reminders.interval_weekdays,
+                 reminders.max_occurrences,
+                 reminders.missed_policy,
+                 reminders.month_anchor,
+                 reminders.month_anchor_day,
                  reminders.name,
+                 reminders.replace_mode,
                  reminders.restartable,
+                 reminders.rrule,
+                 reminders.snooze_buttons,
+                 CAST(reminders.thread_id AS CHAR) AS thread_id,
+                 reminders.thread_title,
+                 reminders.timezone,
                  reminders.tts,
                  reminders.username,
                  reminders.utc_time
@@ -120,6 +168,7 @@ pub async fn import_reminders(
     body: Json<ImportBody>,
     ctx: &State<Context>,
     pool: &State<Pool<MySql>>,
+    wakeup: &State<UnboundedSender<NaiveDateTime>>,
 ) -> JsonResult {
     check_authorization!(cookies, ctx.inner(), id);
 
@@ -138,11 +187,28 @@ pub async fn import_reminders(
                         match channel_id.parse::<u64>() {
                             Ok(channel_id) => {
                                 let reminder = Reminder {
+                                    ack_button: record.ack_button,
+                                    allowed_mentions: record
+                                        .allowed_mentions
+                                        .map(|s| serde_json::from_str(&s).ok())
+                                        .flatten(),
                                     attachment: record.attachment,
                                     attachment_name: record.attachment_name,
+                                    attachments: record
+                                        .attachments
+                                        .map(|s| serde_json::from_str(&s).ok())
+                                        .flatten(),
                                     avatar: record.avatar,
                                     channel: channel_id,
                                     content: record.content,
+                                    content_pool: record
+                                        .content_pool
+                                        .map(|s| serde_json::from_str(&s).ok())
+                                        .flatten(),
+                                    content_pool_cursor: record.content_pool_cursor,
+                                    content_pool_mode: record.content_pool_mode,
+                                    cron: record.cron,
+                                    delete_after: record.delete_after,
                                     embed_author: record.embed_author,
                                     embed_author_url: record.embed_author_url,
                                     embed_color: record.embed_color,
@@ -156,12 +222,39 @@ pub async fn import_reminders(
                                         .embed_fields
                                         .map(|s| serde_json::from_str(&s).ok())
                                         .flatten(),
+                                    embeds: record
+                                        .embeds
+                                        .map(|s| serde_json::from_str(&s).ok())
+                                        .flatten(),
                                     enabled: record.enabled,
+                                    escalate_after: record.escalate_after,
+                                    escalate_limit: record.escalate_limit,
+                                    escalate_mentions: record.escalate_mentions,
+                                    escalate_user: record.escalate_user,
+                                    exclude_dates: record
+                                        .exclude_dates
+                                        .map(|s| serde_json::from_str(&s).ok())
+                                        .flatten(),
                                     expires: record.expires,
+                                    failed: false,
                                     interval_seconds: record.interval_seconds,
                                     interval_months: record.interval_months,
+                                    interval_wallclock: record.interval_wallclock,
+                                    interval_weekdays: record.interval_weekdays,
+                                    max_occurrences: record.max_occurrences,
+                                    missed_policy: record.missed_policy,
+                                    month_anchor: record.month_anchor,
+                                    month_anchor_day: record.month_anchor_day,
                                     name: record.name,
+                                    occurrences: 0,
+                                    replace_mode: record.replace_mode,
                                     restartable: record.restartable,
+                                    rrule: record.rrule,
+                                    skip_next: false,
+                                    snooze_buttons: record.snooze_buttons,
+                                    thread_id: record.thread_id,
+                                    thread_title: record.thread_title,
+                                    timezone: record.timezone,
                                     tts: record.tts,
                                     uid: generate_uid(),
                                     username: record.username,
@@ -171,6 +264,7 @@ pub async fn import_reminders(
                                 create_reminder(
                                     ctx.inner(),
                                     pool.inner(),
+                                    wakeup.inner(),
                                     GuildId(id),
                                     UserId(user_id),
                                     reminder,
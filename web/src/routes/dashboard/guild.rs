@@ -1,29 +1,38 @@
 use std::env;
 
+use chrono::NaiveDateTime;
+use chrono_tz::Tz;
 use rocket::{
     http::CookieJar,
     serde::json::{json, Json},
+    tokio::sync::mpsc::UnboundedSender,
     State,
 };
 use serde::Serialize;
 use serenity::{
     client::Context,
     model::{
-        channel::GuildChannel,
-        id::{ChannelId, GuildId, RoleId},
+        channel::{ChannelType, GuildChannel},
+        id::{ChannelId, GuildId, RoleId, UserId},
     },
 };
 use sqlx::{MySql, Pool};
 
 use crate::{
     consts::{
-        MAX_CONTENT_LENGTH, MAX_EMBED_AUTHOR_LENGTH, MAX_EMBED_DESCRIPTION_LENGTH,
-        MAX_EMBED_FIELDS, MAX_EMBED_FIELD_TITLE_LENGTH, MAX_EMBED_FIELD_VALUE_LENGTH,
-        MAX_EMBED_FOOTER_LENGTH, MAX_EMBED_TITLE_LENGTH, MAX_URL_LENGTH, MAX_USERNAME_LENGTH,
+        CONTENT_POOL_MODES, MAX_CONTENT_LENGTH, MAX_CONTENT_POOL, MAX_EMBED_AUTHOR_LENGTH,
+        MAX_EMBED_DESCRIPTION_LENGTH, MAX_EMBED_FIELDS, MAX_EMBED_FIELD_TITLE_LENGTH,
+        MAX_EMBED_FIELD_VALUE_LENGTH, MAX_EMBED_FOOTER_LENGTH, MAX_EMBED_TITLE_LENGTH,
+        MAX_ESCALATE_MENTIONS_LENGTH, MAX_ESCALATIONS, MAX_HOLIDAY_NAME_LENGTH,
+        MAX_THREAD_TITLE_LENGTH, MAX_URL_LENGTH, MAX_USERNAME_LENGTH, MISSED_POLICIES,
+        MONTH_ANCHORS, REPLACE_MODES,
     },
     routes::dashboard::{
-        create_database_channel, create_reminder, template_name_default, DeleteReminder,
-        DeleteReminderTemplate, JsonResult, PatchReminder, Reminder, ReminderTemplate,
+        can_mention_everyone, check_extras, create_database_channel, create_reminder,
+        is_mention_list, is_thread, local_day, set_reminder_extras, template_name_default,
+        AllowedMentions, DeleteHoliday, DeleteReminder, DeleteReminderTemplate, Holiday,
+        JsonResult, PatchReminder, Reminder, ReminderAttachment, ReminderDelivery,
+        ReminderTemplate,
     },
 };
 
@@ -76,7 +85,9 @@ pub async fn get_guild_channels(
                 .channels
                 .iter()
                 .filter_map(|(id, channel)| channel.to_owned().guild().map(|c| (id.to_owned(), c)))
-                .filter(|(_, channel)| channel.is_text_based())
+                .filter(|(_, channel)| {
+                    channel.is_text_based() || channel.kind == ChannelType::Forum
+                })
                 .collect::<Vec<(ChannelId, GuildChannel)>>();
 
             channels.sort_by(|(_, c1), (_, c2)| c1.position.cmp(&c2.position));
@@ -282,6 +293,94 @@ pub async fn delete_reminder_template(
     }
 }
 
+#[get("/api/guild/<id>/holidays")]
+pub async fn get_holidays(
+    id: u64,
+    cookies: &CookieJar<'_>,
+    ctx: &State<Context>,
+    pool: &State<Pool<MySql>>,
+) -> JsonResult {
+    check_authorization!(cookies, ctx.inner(), id);
+
+    match sqlx::query_as_unchecked!(
+        Holiday,
+        "SELECT id, date, name FROM guild_holidays
+        WHERE guild_id = (SELECT id FROM guilds WHERE guild = ?)
+        ORDER BY date",
+        id
+    )
+    .fetch_all(pool.inner())
+    .await
+    {
+        Ok(holidays) => Ok(json!(holidays)),
+        Err(e) => {
+            warn!("Could not fetch holidays from {}: {:?}", id, e);
+
+            json_err!("Could not get holidays")
+        }
+    }
+}
+
+#[post("/api/guild/<id>/holidays", data = "<holiday>")]
+pub async fn create_holiday(
+    id: u64,
+    holiday: Json<Holiday>,
+    cookies: &CookieJar<'_>,
+    ctx: &State<Context>,
+    pool: &State<Pool<MySql>>,
+) -> JsonResult {
+    check_authorization!(cookies, ctx.inner(), id);
+
+    check_length!(MAX_HOLIDAY_NAME_LENGTH, holiday.name);
+
+    match sqlx::query!(
+        "INSERT INTO guild_holidays (guild_id, date, name)
+        VALUES ((SELECT id FROM guilds WHERE guild = ?), ?, ?)
+        ON DUPLICATE KEY UPDATE name = ?",
+        id,
+        holiday.date,
+        holiday.name,
+        holiday.name
+    )
+    .execute(pool.inner())
+    .await
+    {
+        Ok(_) => Ok(json!({})),
+        Err(e) => {
+            warn!("Could not add holiday to {}: {:?}", id, e);
+
+            json_err!("Could not add holiday")
+        }
+    }
+}
+
+#[delete("/api/guild/<id>/holidays", data = "<delete_holiday>")]
+pub async fn delete_holiday(
+    id: u64,
+    delete_holiday: Json<DeleteHoliday>,
+    cookies: &CookieJar<'_>,
+    ctx: &State<Context>,
+    pool: &State<Pool<MySql>>,
+) -> JsonResult {
+    check_authorization!(cookies, ctx.inner(), id);
+
+    match sqlx::query!(
+        "DELETE FROM guild_holidays WHERE guild_id = (SELECT id FROM guilds WHERE guild = ?) AND id = ?",
+        id,
+        delete_holiday.id
+    )
+    .execute(pool.inner())
+    .await
+    {
+        Ok(_) => Ok(json!({})),
+        Err(e) => {
+            warn!("Could not delete holiday from {}: {:?}", id, e);
+
+            json_err!("Could not delete holiday")
+        }
+    }
+}
+
 #[post("/api/guild/<id>/reminders", data = "<reminder>")]
 pub async fn create_guild_reminder(
     id: u64,
@@ -289,6 +388,7 @@ pub async fn create_guild_reminder(
     cookies: &CookieJar<'_>,
     serenity_context: &State<Context>,
     pool: &State<Pool<MySql>>,
+    wakeup: &State<UnboundedSender<NaiveDateTime>>,
 ) -> JsonResult {
     check_authorization!(cookies, serenity_context.inner(), id);
 
@@ -298,6 +398,7 @@ pub async fn create_guild_reminder(
     create_reminder(
         serenity_context.inner(),
         pool.inner(),
+        wakeup.inner(),
         GuildId(id),
         UserId(user_id),
         reminder.into_inner(),
@@ -321,11 +422,26 @@ pub async fn get_reminders(id: u64, ctx: &State<Context>, pool: &State<Pool<MySq
             sqlx::query_as_unchecked!(
                 Reminder,
                 "SELECT
+                 reminders.ack_button,
+                 reminders.allowed_mentions,
                  reminders.attachment,
                  reminders.attachment_name,
+                 (
+                     SELECT JSON_ARRAYAGG(JSON_OBJECT(
+                         'id', reminder_attachments.id,
+                         'name', reminder_attachments.name,
+                         'size', LENGTH(reminder_attachments.data)
+                     ))
+                     FROM reminder_attachments WHERE reminder_attachments.reminder_id = reminders.id
+                 ) AS attachments,
                  reminders.avatar,
                  channels.channel,
                  reminders.content,
+                 reminders.content_pool,
+                 reminders.content_pool_cursor,
+                 reminders.content_pool_mode,
+                 reminders.cron,
+                 reminders.delete_after,
                  reminders.embed_author,
                  reminders.embed_author_url,
                  reminders.embed_color,
@@ -336,12 +452,47 @@ pub async fn get_reminders(id: u64, ctx: &State<Context>, pool: &State<Pool<MySq
                  reminders.embed_thumbnail_url,
                  reminders.embed_title,
                  reminders.embed_fields,
+                 (
+                     SELECT JSON_ARRAYAGG(JSON_OBJECT(
+                         'author', reminder_embeds.author,
+                         'author_url', reminder_embeds.author_url,
+                         'color', reminder_embeds.color,
+                         'description', reminder_embeds.description,
+                         'fields', IFNULL(reminder_embeds.fields, JSON_ARRAY()),
+                         'footer', reminder_embeds.footer,
+                         'footer_url', reminder_embeds.footer_url,
+                         'image_url', reminder_embeds.image_url,
+                         'thumbnail_url', reminder_embeds.thumbnail_url,
+                         'title', reminder_embeds.title
+                     ))
+                     FROM reminder_embeds WHERE reminder_embeds.reminder_id = reminders.id
+                 ) AS embeds,
                  reminders.enabled,
+                 reminders.escalate_after,
+                 reminders.escalate_limit,
+                 reminders.escalate_mentions,
+                 CAST(reminders.escalate_user AS CHAR) AS escalate_user,
+                 reminders.exclude_dates,
                  reminders.expires,
+                 reminders.failed,
                  reminders.interval_seconds,
                  reminders.interval_months,
+                 reminders.interval_wallclock,
+                 reminders.interval_weekdays,
+                 reminders.max_occurrences,
+                 reminders.missed_policy,
+                 reminders.month_anchor,
+                 reminders.month_anchor_day,
                  reminders.name,
+                 reminders.occurrences,
+                 reminders.replace_mode,
                  reminders.restartable,
+                 reminders.rrule,
+                 reminders.skip_next,
+                 reminders.snooze_buttons,
+                 CAST(reminders.thread_id AS CHAR) AS thread_id,
+                 reminders.thread_title,
+                 reminders.timezone,
                  reminders.tts,
                  reminders.uid,
                  reminders.username,
@@ -368,20 +519,363 @@ pub async fn get_reminders(id: u64, ctx: &State<Context>, pool: &State<Pool<MySq
     }
 }
 
+#[get("/api/guild/<id>/reminders/<uid>/deliveries")]
+pub async fn get_reminder_deliveries(
+    id: u64,
+    uid: String,
+    cookies: &CookieJar<'_>,
+    ctx: &State<Context>,
+    pool: &State<Pool<MySql>>,
+) -> JsonResult {
+    check_authorization!(cookies, ctx.inner(), id);
+
+    match GuildId(id).channels(&ctx.inner()).await {
+        Ok(channels) => {
+            let channels = channels
+                .keys()
+                .into_iter()
+                .map(|k| k.as_u64().to_string())
+                .collect::<Vec<String>>()
+                .join(",");
+
+            sqlx::query_as_unchecked!(
+                ReminderDelivery,
+                "SELECT
+                 channel,
+                 CAST(thread_id AS CHAR) AS thread_id,
+                 CAST(message_id AS CHAR) AS message_id,
+                 method,
+                 sent_at,
+                 error,
+                 content
+                FROM reminder_deliveries
+                WHERE reminder_uid = ? AND FIND_IN_SET(channel, ?)
+                ORDER BY sent_at DESC, id DESC
+                LIMIT 100",
+                uid,
+                channels
+            )
+            .fetch_all(pool.inner())
+            .await
+            .map(|r| Ok(json!(r)))
+            .unwrap_or_else(|e| {
+                warn!("Failed to complete SQL query: {:?}", e);
+
+                json_err!("Could not load deliveries")
+            })
+        }
+        Err(e) => {
+            warn!("Could not fetch channels from {}: {:?}", id, e);
+
+            Ok(json!([]))
+        }
+    }
+}
+
+/// One of the attachments sent after a reminder's own. Reminders are listed with only the id, name
+/// and size of these, so their data is fetched here when needed.
+#[get("/api/guild/<id>/reminders/<uid>/attachments/<attachment>")]
+pub async fn get_reminder_attachment(
+    id: u64,
+    uid: String,
+    attachment: u32,
+    cookies: &CookieJar<'_>,
+    ctx: &State<Context>,
+    pool: &State<Pool<MySql>>,
+) -> JsonResult {
+    check_authorization!(cookies, ctx.inner(), id);
+
+    match GuildId(id).channels(&ctx.inner()).await {
+        Ok(channels) => {
+            let channels = channels
+                .keys()
+                .into_iter()
+                .map(|k| k.as_u64().to_string())
+                .collect::<Vec<String>>()
+                .join(",");
+
+            match sqlx::query_as_unchecked!(
+                ReminderAttachment,
+                "SELECT
+                 reminder_attachments.id,
+                 reminder_attachments.name,
+                 REPLACE(TO_BASE64(reminder_attachments.data), '\\n', '') AS data,
+                 LENGTH(reminder_attachments.data) AS size
+                FROM reminder_attachments
+                INNER JOIN reminders ON reminders.id = reminder_attachments.reminder_id
+                INNER JOIN channels ON channels.id = reminders.channel_id
+                WHERE reminder_attachments.id = ? AND reminders.uid = ?
+                    AND FIND_IN_SET(channels.channel, ?)",
+                attachment,
+                uid,
+                channels
+            )
+            .fetch_optional(pool.inner())
+            .await
+            {
+                Ok(Some(attachment)) => Ok(json!(attachment)),
+                Ok(None) => json_err!("Attachment not found"),
+                Err(e) => {
+                    warn!("Failed to complete SQL query: {:?}", e);
+
+                    json_err!("Could not load attachment")
+                }
+            }
+        }
+        Err(e) => {
+            warn!("Could not fetch channels from {}: {:?}", id, e);
+
+            json_err!("Could not load attachment")
+        }
+    }
+}
+
+#[post("/api/guild/<id>/reminders/<uid>/skip")]
+pub async fn skip_reminder(
+    id: u64,
+    uid: String,
+    cookies: &CookieJar<'_>,
+    ctx: &State<Context>,
+    pool: &State<Pool<MySql>>,
+) -> JsonResult {
+    check_authorization!(cookies, ctx.inner(), id);
+
+    match sqlx::query!(
+        "UPDATE reminders
+        INNER JOIN channels ON channels.id = reminders.channel_id
+        SET reminders.skip_next = 1
+        WHERE
+            reminders.uid = ?
+            AND channels.guild_id = (SELECT id FROM guilds WHERE guild = ?)
+            AND (
+                reminders.interval_seconds IS NOT NULL
+                OR reminders.interval_months IS NOT NULL
+                OR reminders.cron IS NOT NULL
+                OR reminders.rrule IS NOT NULL
+            )",
+        uid,
+        id
+    )
+    .execute(pool.inner())
+    .await
+    {
+        Ok(result) if result.rows_affected() > 0 => Ok(json!({})),
+        Ok(_) => json_err!("Only repeating reminders can be skipped"),
+        Err(e) => {
+            warn!("Error in `skip_reminder`: {:?}", e);
+
+            json_err!("Could not skip reminder")
+        }
+    }
+}
+
 #[patch("/api/guild/<id>/reminders", data = "<reminder>")]
 pub async fn edit_reminder(
     id: u64,
     reminder: Json<PatchReminder>,
+    cookies: &CookieJar<'_>,
     serenity_context: &State<Context>,
     pool: &State<Pool<MySql>>,
+    wakeup: &State<UnboundedSender<NaiveDateTime>>,
 ) -> JsonResult {
+    check_authorization!(cookies, serenity_context.inner(), id);
+
+    let user_id =
+        cookies.get_private("userid").map(|c| c.value().parse::<u64>().ok()).flatten().unwrap();
+
+    // mentions are checked against the channel the reminder will be sent to
+    let channel = if reminder.channel > 0 {
+        ChannelId(reminder.channel)
+    } else {
+        match sqlx::query!(
+            "SELECT channels.channel FROM reminders
+            INNER JOIN channels ON channels.id = reminders.channel_id
+            WHERE reminders.uid = ?",
+            reminder.uid
+        )
+        .fetch_one(pool.inner())
+        .await
+        {
+            Ok(row) => ChannelId(row.channel),
+            Err(e) => {
+                warn!("Error in `edit_reminder`: Could not fetch channel: {:?}", e);
+
+                return Err(json!({"error": "Could not load reminder"}));
+            }
+        }
+    };
+
+    if let Some(allowed_mentions) = &reminder.allowed_mentions {
+        if let Some(mentions) = allowed_mentions {
+            mentions.0.validate()?;
+        }
+
+        // leaving mentions unrestricted also allows @everyone
+        if allowed_mentions.as_ref().map_or(true, |mentions| mentions.0.allows_everyone()) {
+            if !can_mention_everyone(
+                serenity_context.inner(),
+                GuildId(id),
+                channel,
+                UserId(user_id),
+            )
+            .await
+            {
+                return Err(
+                    json!({"error": "You need the Mention Everyone permission to allow @everyone"}),
+                );
+            }
+        }
+    }
+
+    if let Some(Some(policy)) = &reminder.missed_policy {
+        if !MISSED_POLICIES.contains(&policy.as_str()) {
+            return Err(json!({"error": "Invalid missed reminder policy"}));
+        }
+    }
+
+    if let Some(Some(anchor)) = &reminder.month_anchor {
+        if !MONTH_ANCHORS.contains(&anchor.as_str()) {
+            return Err(json!({"error": "Invalid monthly anchor"}));
+        }
+    }
+
+    if let Some(Some(mode)) = &reminder.replace_mode {
+        if !REPLACE_MODES.contains(&mode.as_str()) {
+            return Err(json!({"error": "Replace mode not recognised"}));
+        }
+    }
+
+    if let Some(Some(thread_title)) = &reminder.thread_title {
+        check_length!(MAX_THREAD_TITLE_LENGTH, thread_title);
+        check_template!(thread_title);
+    }
+
+    if let Some(Some(variants)) = &reminder.content_pool {
+        if variants.0.len() > MAX_CONTENT_POOL {
+            return Err(json!({
+                "error": format!("Content pool can hold at most {} variants", MAX_CONTENT_POOL)
+            }));
+        }
+
+        for variant in &variants.0 {
+            check_length!(MAX_CONTENT_LENGTH, variant);
+            check_template!(variant);
+        }
+    }
+
+    if let Some(mode) = &reminder.content_pool_mode {
+        if !CONTENT_POOL_MODES.contains(&mode.as_str()) {
+            return Err(json!({"error": "Content pool mode not recognised"}));
+        }
+    }
+
+    check_template_opt!(
+        reminder.content,
+        reminder.embed_title,
+        reminder.embed_description,
+        reminder.embed_footer
+    );
+    if let Some(fields) = &reminder.embed_fields {
+        for field in &fields.0 {
+            check_template!(field.title, field.value);
+        }
+    }
+
+    check_extras(
+        reminder.attachments.as_deref().unwrap_or_default(),
+        reminder.embeds.as_deref().unwrap_or_default(),
+    )?;
+
+    if let Some(Some(thread_id)) = &reminder.thread_id {
+        let thread = match thread_id.parse::<u64>() {
+            Ok(thread_id) => ChannelId(thread_id).to_channel(serenity_context.inner()).await.ok(),
+            Err(_) => None,
+        };
+
+        if thread.and_then(|t| t.guild()).map_or(true, |t| t.guild_id.0 != id || !is_thread(t.kind))
+        {
+            return Err(json!({"error": "Thread not found"}));
+        }
+    }
+
+    if let Some(Some(0)) = reminder.delete_after {
+        return Err(json!({"error": "Delete after must be at least 1 second"}));
+    }
+
+    if let Some(Some(0)) = reminder.escalate_after {
+        return Err(json!({"error": "Escalate after must be at least 1 second"}));
+    }
+
+    if let Some(limit) = reminder.escalate_limit {
+        if limit < 1 || limit > MAX_ESCALATIONS {
+            return Err(json!({
+                "error": format!("Escalation limit must be between 1 and {}", MAX_ESCALATIONS)
+            }));
+        }
+    }
+
+    if let Some(Some(mentions)) = &reminder.escalate_mentions {
+        check_length!(MAX_ESCALATE_MENTIONS_LENGTH, mentions);
+
+        if !is_mention_list(mentions) {
+            return Err(json!({"error": "Escalation mentions must be role or user mentions"}));
+        }
+    }
+
+    if let Some(Some(user)) = &reminder.escalate_user {
+        if user.parse::<u64>().is_err() {
+            return Err(json!({"error": "Invalid backup user"}));
+        }
+    }
+
+    if let Some(Some(0)) = reminder.max_occurrences {
+        return Err(json!({"error": "Occurrence limit must be at least 1"}));
+    }
+
+    if let Some(Some(day)) = reminder.month_anchor_day {
+        if day < 1 || day > 31 {
+            return Err(json!({"error": "Invalid monthly anchor day"}));
+        }
+    }
+
+    if let Some(timezone) = &reminder.timezone {
+        if timezone.parse::<Tz>().is_err() {
+            return Err(json!({"error": "Timezone not recognised"}));
+        }
+    }
+
+    if let Some(Some(cron)) = &reminder.cron {
+        // cron schedules run in the reminder's timezone, which is only sent when it changes
+        let timezone = match &reminder.timezone {
+            Some(timezone) => timezone.parse::<Tz>().ok(),
+            None => sqlx::query!("SELECT timezone FROM reminders WHERE uid = ?", reminder.uid)
+                .fetch_one(pool.inner())
+                .await
+                .ok()
+                .and_then(|row| row.timezone.parse::<Tz>().ok()),
+        };
+
+        check_cron!(cron, timezone.unwrap_or(Tz::UTC));
+    }
+
+    if let Some(Some(rrule)) = &reminder.rrule {
+        check_rrule!(rrule);
+    }
+
     let mut error = vec![];
 
     update_field!(pool.inner(), error, reminder.[
+        ack_button,
+        allowed_mentions,
         attachment,
         attachment_name,
         avatar,
         content,
+        content_pool,
+        content_pool_cursor,
+        content_pool_mode,
+        cron,
+        delete_after,
         embed_author,
         embed_author_url,
         embed_color,
@@ -393,16 +887,143 @@ pub async fn edit_reminder(
         embed_title,
         embed_fields,
         enabled,
+        escalate_after,
+        escalate_limit,
+        escalate_mentions,
+        escalate_user,
+        exclude_dates,
         expires,
         interval_seconds,
         interval_months,
+        interval_wallclock,
+        interval_weekdays,
+        max_occurrences,
+        missed_policy,
+        month_anchor,
+        month_anchor_day,
         name,
+        replace_mode,
         restartable,
+        rrule,
+        skip_next,
+        snooze_buttons,
+        thread_id,
+        thread_title,
+        timezone,
         tts,
         username,
         utc_time
     ]);
 
+    // new text is sent with the stored mentions, so if those allow @everyone and this user can't,
+    // restrict them as a new reminder's would be
+    let text_changed = reminder.content.is_some()
+        || reminder.content_pool.is_some()
+        || reminder.embed_title.is_some()
+        || reminder.embed_description.is_some()
+        || reminder.embed_footer.is_some()
+        || reminder.embed_fields.is_some()
+        || reminder.embeds.is_some();
+
+    if text_changed
+        && reminder.allowed_mentions.is_none()
+        && !can_mention_everyone(serenity_context.inner(), GuildId(id), channel, UserId(user_id))
+            .await
+    {
+        if let Err(e) = sqlx::query!(
+            "UPDATE reminders SET allowed_mentions = ?
+            WHERE uid = ? AND (
+                allowed_mentions IS NULL
+                OR JSON_CONTAINS(allowed_mentions, '\"everyone\"', '$.parse')
+            )",
+            sqlx::types::Json(AllowedMentions::users_and_roles()),
+            reminder.uid
+        )
+        .execute(pool.inner())
+        .await
+        {
+            warn!("Error restricting allowed mentions: {:?}", e);
+
+            error.push("Couldn't restrict allowed mentions".to_string())
+        }
+    }
+
+    if let Err(e) = set_reminder_extras(
+        pool.inner(),
+        &reminder.uid,
+        reminder.attachments.as_deref(),
+        reminder.embeds.as_deref(),
+    )
+    .await
+    {
+        warn!("Error saving attachments and embeds: {:?}", e);
+
+        error.push("Couldn't save attachments and embeds".to_string())
+    }
+
+    // a new time gives a failed reminder another chance
+    if reminder.utc_time.is_some() {
+        match sqlx::query!(
+            "UPDATE reminders SET failed = 0, retry_count = 0, retry_at = NULL WHERE uid = ?",
+            reminder.uid
+        )
+        .execute(pool.inner())
+        .await
+        {
+            Ok(_) => {}
+            Err(e) => {
+                warn!("Error resetting failed reminder: {:?}", e);
+
+                error.push("Couldn't reset failed reminder".to_string())
+            }
+        }
+    }
+
+    // a new rule starts counting occurrences again
+    if reminder.rrule.is_some() {
+        match sqlx::query!("UPDATE reminders SET occurrences = 0 WHERE uid = ?", reminder.uid)
+            .execute(pool.inner())
+            .await
+        {
+            Ok(_) => {}
+            Err(e) => {
+                warn!("Error resetting occurrences: {:?}", e);
+
+                error.push("Couldn't reset occurrences".to_string())
+            }
+        }
+    }
+
+    // clamped reminders keep the day they were set on, in the reminder's timezone
+    if matches!(reminder.month_anchor, Some(Some(_)))
+        && reminder.month_anchor_day.flatten().is_none()
+    {
+        match sqlx::query!("SELECT utc_time, timezone FROM reminders WHERE uid = ?", reminder.uid)
+            .fetch_one(pool.inner())
+            .await
+        {
+            Ok(row) => {
+                if let Err(e) = sqlx::query!(
+                    "UPDATE reminders SET month_anchor_day = ? WHERE uid = ?",
+                    local_day(row.utc_time, &row.timezone),
+                    reminder.uid
+                )
+                .execute(pool.inner())
+                .await
+                {
+                    warn!("Error setting monthly anchor day: {:?}", e);
+
+                    error.push("Couldn't set monthly anchor day".to_string())
+                }
+            }
+            Err(e) => {
+                warn!("Error fetching reminder for monthly anchor day: {:?}", e);
+
+                error.push("Couldn't set monthly anchor day".to_string())
+            }
+        }
+    }
+
     if reminder.channel > 0 {
         let channel = ChannelId(reminder.channel).to_channel_cached(&serenity_context.inner());
         match channel {
@@ -465,11 +1086,26 @@ pub async fn edit_reminder(
 
     match sqlx::query_as_unchecked!(
         Reminder,
-        "SELECT reminders.attachment,
+        "SELECT reminders.ack_button,
+         reminders.allowed_mentions,
+         reminders.attachment,
          reminders.attachment_name,
+         (
+             SELECT JSON_ARRAYAGG(JSON_OBJECT(
+                 'id', reminder_attachments.id,
+                 'name', reminder_attachments.name,
+                 'size', LENGTH(reminder_attachments.data)
+             ))
+             FROM reminder_attachments WHERE reminder_attachments.reminder_id = reminders.id
+         ) AS attachments,
          reminders.avatar,
          channels.channel,
          reminders.content,
+         reminders.content_pool,
+         reminders.content_pool_cursor,
+         reminders.content_pool_mode,
+         reminders.cron,
+         reminders.delete_after,
          reminders.embed_author,
          reminders.embed_author_url,
          reminders.embed_color,
@@ -480,12 +1116,47 @@ pub async fn edit_reminder(
          reminders.embed_thumbnail_url,
          reminders.embed_title,
          reminders.embed_fields,
+         (
+             SELECT JSON_ARRAYAGG(JSON_OBJECT(
+                 'author', reminder_embeds.author,
+                 'author_url', reminder_embeds.author_url,
+                 'color', reminder_embeds.color,
+                 'description', reminder_embeds.description,
+                 'fields', IFNULL(reminder_embeds.fields, JSON_ARRAY()),
+                 'footer', reminder_embeds.footer,
+                 'footer_url', reminder_embeds.footer_url,
+                 'image_url', reminder_embeds.image_url,
+                 'thumbnail_url', reminder_embeds.thumbnail_url,
+                 'title', reminder_embeds.title
+             ))
+             FROM reminder_embeds WHERE reminder_embeds.reminder_id = reminders.id
+         ) AS embeds,
          reminders.enabled,
+         reminders.escalate_after,
+         reminders.escalate_limit,
+         reminders.escalate_mentions,
+         CAST(reminders.escalate_user AS CHAR) AS escalate_user,
+         reminders.exclude_dates,
          reminders.expires,
+         reminders.failed,
          reminders.interval_seconds,
          reminders.interval_months,
+         reminders.interval_wallclock,
+         reminders.interval_weekdays,
+         reminders.max_occurrences,
+         reminders.missed_policy,
+         reminders.month_anchor,
+         reminders.month_anchor_day,
          reminders.name,
+         reminders.occurrences,
+         reminders.replace_mode,
          reminders.restartable,
+         reminders.rrule,
+         reminders.skip_next,
+         reminders.snooze_buttons,
+         CAST(reminders.thread_id AS CHAR) AS thread_id,
+         reminders.thread_title,
+         reminders.timezone,
          reminders.tts,
          reminders.uid,
          reminders.username,
@@ -498,7 +1169,11 @@ pub async fn edit_reminder(
     .fetch_one(pool.inner())
     .await
     {
-        Ok(reminder) => Ok(json!({"reminder": reminder, "errors": error})),
+        Ok(reminder) => {
+            let _ = wakeup.send(reminder.utc_time);
+
+            Ok(json!({"reminder": reminder, "errors": error}))
+        }
 
         Err(e) => {
             warn!("Error exiting `edit_reminder': {:?}", e);
@@ -1,28 +1,39 @@
 use std::collections::HashMap;
 
-use chrono::{naive::NaiveDateTime, Utc};
+use chrono::{
+    naive::{NaiveDate, NaiveDateTime},
+    Datelike, TimeZone, Utc,
+};
+use chrono_tz::Tz;
 use rand::{rngs::OsRng, seq::IteratorRandom};
 use rocket::{
     http::CookieJar,
     response::Redirect,
     serde::json::{json, Value as JsonValue},
+    tokio::sync::mpsc::UnboundedSender,
 };
 use rocket_dyn_templates::Template;
 use serde::{Deserialize, Serialize};
 use serenity::{
     client::Context,
     http::Http,
-    model::id::{ChannelId, GuildId, UserId},
+    model::{
+        channel::ChannelType,
+        id::{ChannelId, GuildId, UserId},
+    },
 };
 use sqlx::{types::Json, Executor, MySql, Pool};
 
 use crate::{
     check_guild_subscription, check_subscription,
     consts::{
-        CHARACTERS, DAY, DEFAULT_AVATAR, MAX_CONTENT_LENGTH, MAX_EMBED_AUTHOR_LENGTH,
-        MAX_EMBED_DESCRIPTION_LENGTH, MAX_EMBED_FIELDS, MAX_EMBED_FIELD_TITLE_LENGTH,
-        MAX_EMBED_FIELD_VALUE_LENGTH, MAX_EMBED_FOOTER_LENGTH, MAX_EMBED_TITLE_LENGTH,
-        MAX_URL_LENGTH, MAX_USERNAME_LENGTH, MIN_INTERVAL,
+        CHARACTERS, CONTENT_POOL_MODES, DAY, DEFAULT_AVATAR, MAX_ALLOWED_MENTIONS, MAX_ATTACHMENTS,
+        MAX_ATTACHMENT_NAME_LENGTH, MAX_CONTENT_LENGTH, MAX_CONTENT_POOL, MAX_EMBEDS,
+        MAX_EMBED_AUTHOR_LENGTH, MAX_EMBED_DESCRIPTION_LENGTH, MAX_EMBED_FIELDS,
+        MAX_EMBED_FIELD_TITLE_LENGTH, MAX_EMBED_FIELD_VALUE_LENGTH, MAX_EMBED_FOOTER_LENGTH,
+        MAX_EMBED_TITLE_LENGTH, MAX_ESCALATE_MENTIONS_LENGTH, MAX_ESCALATIONS,
+        MAX_THREAD_TITLE_LENGTH, MAX_URL_LENGTH, MAX_USERNAME_LENGTH, MIN_INTERVAL,
+        MISSED_POLICIES, MONTH_ANCHORS, REPLACE_MODES,
     },
     Database, Error,
 };
@@ -38,6 +49,23 @@ fn name_default() -> String {
     "Reminder".to_string()
 }
 
+fn content_pool_mode_default() -> String {
+    "sequential".to_string()
+}
+
+fn escalate_limit_default() -> u32 {
+    1
+}
+
+fn timezone_default() -> String {
+    "UTC".to_string()
+}
+
+/// The day of the month of a UTC time in `timezone`.
+fn local_day(utc_time: NaiveDateTime, timezone: &str) -> u32 {
+    Utc.from_utc_datetime(&utc_time).with_timezone(&timezone.parse().unwrap_or(Tz::UTC)).day()
+}
+
 fn template_name_default() -> String {
     "Template".to_string()
 }
@@ -103,6 +131,20 @@ pub struct DeleteReminderTemplate {
     id: u32,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct Holiday {
+    #[serde(default = "id_default")]
+    id: u32,
+    date: NaiveDate,
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteHoliday {
+    id: u32,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct EmbedField {
     title: String,
@@ -110,15 +152,304 @@ pub struct EmbedField {
     inline: bool,
 }
 
+/// A file sent after the reminder's own attachment. Reminders are listed without the data, which
+/// is fetched from `get_reminder_attachment`. When saving, an attachment without data keeps the
+/// data of the reminder's attachment with the given id.
+#[derive(Serialize, Deserialize)]
+pub struct ReminderAttachment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<u32>,
+    name: String,
+    /// Base64 encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
+    /// Bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size: Option<u32>,
+}
+
+impl ReminderAttachment {
+    fn validate(&self) -> Result<(), JsonValue> {
+        check_length!(MAX_ATTACHMENT_NAME_LENGTH, self.name);
+
+        match &self.data {
+            Some(data) if base64::decode(data).is_err() => {
+                Err(json!({"error": "Attachment data must be base64 encoded"}))
+            }
+            None if self.id.is_none() => {
+                Err(json!({"error": "Attachments need data or the id of an existing attachment"}))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An embed sent after the reminder's own embed
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReminderEmbed {
+    author: String,
+    author_url: Option<String>,
+    color: u32,
+    description: String,
+    fields: Vec<EmbedField>,
+    footer: String,
+    footer_url: Option<String>,
+    image_url: Option<String>,
+    thumbnail_url: Option<String>,
+    title: String,
+}
+
+impl ReminderEmbed {
+    fn validate(&self) -> Result<(), JsonValue> {
+        check_length!(MAX_EMBED_DESCRIPTION_LENGTH, self.description);
+        check_length!(MAX_EMBED_TITLE_LENGTH, self.title);
+        check_length!(MAX_EMBED_AUTHOR_LENGTH, self.author);
+        check_length!(MAX_EMBED_FOOTER_LENGTH, self.footer);
+        check_length!(MAX_EMBED_FIELDS, self.fields);
+        for field in &self.fields {
+            check_length!(MAX_EMBED_FIELD_VALUE_LENGTH, field.value);
+            check_length!(MAX_EMBED_FIELD_TITLE_LENGTH, field.title);
+            check_template!(field.title, field.value);
+        }
+        check_length_opt!(
+            MAX_URL_LENGTH,
+            self.footer_url,
+            self.thumbnail_url,
+            self.author_url,
+            self.image_url
+        );
+        check_url_opt!(self.footer_url, self.thumbnail_url, self.author_url, self.image_url);
+        check_template!(self.title, self.description, self.footer);
+
+        Ok(())
+    }
+}
+
+/// Check the attachments and embeds sent after a reminder's own.
+fn check_extras(
+    attachments: &[ReminderAttachment],
+    embeds: &[ReminderEmbed],
+) -> Result<(), JsonValue> {
+    if attachments.len() >= MAX_ATTACHMENTS {
+        return Err(json!({
+            "error": format!("A reminder can have at most {} attachments", MAX_ATTACHMENTS)
+        }));
+    }
+
+    if embeds.len() >= MAX_EMBEDS {
+        return Err(json!({
+            "error": format!("A reminder can have at most {} embeds", MAX_EMBEDS)
+        }));
+    }
+
+    for attachment in attachments {
+        attachment.validate()?;
+    }
+
+    for embed in embeds {
+        embed.validate()?;
+    }
+
+    Ok(())
+}
+
+/// Replace the attachments and embeds sent after a reminder's own, where given. Either all of
+/// them are replaced or none are.
+async fn set_reminder_extras(
+    pool: &Pool<MySql>,
+    uid: &str,
+    attachments: Option<&[ReminderAttachment]>,
+    embeds: Option<&[ReminderEmbed]>,
+) -> Result<(), sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+
+    if let Some(attachments) = attachments {
+        // the new attachments are added after the old ones, which are then removed, so that kept
+        // attachments can be copied across in order
+        let replaced = sqlx::query!(
+            "SELECT MAX(reminder_attachments.id) AS id FROM reminder_attachments
+            INNER JOIN reminders ON reminders.id = reminder_attachments.reminder_id
+            WHERE reminders.uid = ?",
+            uid
+        )
+        .fetch_one(&mut transaction)
+        .await?
+        .id;
+
+        for attachment in attachments {
+            match (&attachment.data, attachment.id) {
+                (Some(data), _) => {
+                    // checked by `ReminderAttachment::validate`
+                    let data = base64::decode(data).unwrap_or_default();
+
+                    sqlx::query!(
+                        "INSERT INTO reminder_attachments (reminder_id, name, data)
+                        SELECT id, ?, ? FROM reminders WHERE uid = ?",
+                        attachment.name,
+                        data,
+                        uid
+                    )
+                    .execute(&mut transaction)
+                    .await?;
+                }
+
+                (None, Some(id)) => {
+                    sqlx::query!(
+                        "INSERT INTO reminder_attachments (reminder_id, name, data)
+                        SELECT reminders.id, ?, reminder_attachments.data
+                        FROM reminder_attachments
+                        INNER JOIN reminders ON reminders.id = reminder_attachments.reminder_id
+                        WHERE reminder_attachments.id = ? AND reminders.uid = ?",
+                        attachment.name,
+                        id,
+                        uid
+                    )
+                    .execute(&mut transaction)
+                    .await?;
+                }
+
+                (None, None) => {}
+            }
+        }
+
+        if let Some(replaced) = replaced {
+            sqlx::query!(
+                "DELETE reminder_attachments FROM reminder_attachments
+                INNER JOIN reminders ON reminders.id = reminder_attachments.reminder_id
+                WHERE reminders.uid = ? AND reminder_attachments.id <= ?",
+                uid,
+                replaced
+            )
+            .execute(&mut transaction)
+            .await?;
+        }
+    }
+
+    if let Some(embeds) = embeds {
+        sqlx::query!(
+            "DELETE reminder_embeds FROM reminder_embeds
+            INNER JOIN reminders ON reminders.id = reminder_embeds.reminder_id
+            WHERE reminders.uid = ?",
+            uid
+        )
+        .execute(&mut transaction)
+        .await?;
+
+        for embed in embeds {
+            sqlx::query!(
+                "INSERT INTO reminder_embeds (
+                 reminder_id,
+                 title,
+                 description,
+                 image_url,
+                 thumbnail_url,
+                 footer,
+                 footer_url,
+                 author,
+                 author_url,
+                 color,
+                 fields
+                ) SELECT id, ?, ?, ?, ?, ?, ?, ?, ?, ?, ? FROM reminders WHERE uid = ?",
+                embed.title,
+                embed.description,
+                embed.image_url,
+                embed.thumbnail_url,
+                embed.footer,
+                embed.footer_url,
+                embed.author,
+                embed.author_url,
+                embed.color,
+                Json(&embed.fields),
+                uid
+            )
+            .execute(&mut transaction)
+            .await?;
+        }
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AllowedMentions {
+    #[serde(default)]
+    parse: Vec<String>,
+    #[serde(default)]
+    users: Vec<String>,
+    #[serde(default)]
+    roles: Vec<String>,
+}
+
+impl AllowedMentions {
+    fn users_and_roles() -> Self {
+        AllowedMentions {
+            parse: vec!["users".to_string(), "roles".to_string()],
+            users: vec![],
+            roles: vec![],
+        }
+    }
+
+    fn allows_everyone(&self) -> bool {
+        self.parse.iter().any(|p| p == "everyone")
+    }
+
+    fn validate(&self) -> Result<(), JsonValue> {
+        if self.parse.iter().any(|p| !["users", "roles", "everyone"].contains(&p.as_str())) {
+            return Err(json!({"error": "Allowed mentions not recognised"}));
+        }
+
+        if self.users.iter().chain(&self.roles).any(|id| id.parse::<u64>().is_err()) {
+            return Err(json!({"error": "Allowed mentions must list user and role IDs"}));
+        }
+
+        // Discord rejects a type that is both parsed and listed
+        if (!self.users.is_empty() && self.parse.iter().any(|p| p == "users"))
+            || (!self.roles.is_empty() && self.parse.iter().any(|p| p == "roles"))
+        {
+            return Err(
+                json!({"error": "Allowed mentions can't list a type that is already parsed"}),
+            );
+        }
+
+        if self.users.len() > MAX_ALLOWED_MENTIONS || self.roles.len() > MAX_ALLOWED_MENTIONS {
+            return Err(json!({
+                "error": format!(
+                    "Allowed mentions can list at most {} users and {} roles",
+                    MAX_ALLOWED_MENTIONS, MAX_ALLOWED_MENTIONS
+                )
+            }));
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Reminder {
+    #[serde(default)]
+    ack_button: bool,
+    #[serde(default)]
+    allowed_mentions: Option<Json<AllowedMentions>>,
     #[serde(with = "base64s")]
     attachment: Option<Vec<u8>>,
     attachment_name: Option<String>,
+    #[serde(default)]
+    attachments: Option<Json<Vec<ReminderAttachment>>>,
     avatar: Option<String>,
     #[serde(with = "string")]
     channel: u64,
     content: String,
+    #[serde(default)]
+    content_pool: Option<Json<Vec<String>>>,
+    #[serde(default)]
+    content_pool_cursor: u32,
+    #[serde(default = "content_pool_mode_default")]
+    content_pool_mode: String,
+    cron: Option<String>,
+    delete_after: Option<u32>,
     embed_author: String,
     embed_author_url: Option<String>,
     embed_color: u32,
@@ -129,13 +460,48 @@ pub struct Reminder {
     embed_thumbnail_url: Option<String>,
     embed_title: String,
     embed_fields: Option<Json<Vec<EmbedField>>>,
+    #[serde(default)]
+    embeds: Option<Json<Vec<ReminderEmbed>>>,
     enabled: bool,
+    #[serde(default)]
+    escalate_after: Option<u32>,
+    #[serde(default = "escalate_limit_default")]
+    escalate_limit: u32,
+    #[serde(default)]
+    escalate_mentions: Option<String>,
+    #[serde(default)]
+    escalate_user: Option<String>,
+    exclude_dates: Option<Json<Vec<NaiveDate>>>,
     expires: Option<NaiveDateTime>,
+    #[serde(default)]
+    failed: bool,
     interval_seconds: Option<u32>,
     interval_months: Option<u32>,
+    #[serde(default)]
+    interval_wallclock: bool,
+    #[serde(default)]
+    interval_weekdays: bool,
+    max_occurrences: Option<u32>,
+    missed_policy: Option<String>,
+    month_anchor: Option<String>,
+    month_anchor_day: Option<u32>,
     #[serde(default = "name_default")]
     name: String,
+    #[serde(default)]
+    occurrences: u32,
+    replace_mode: Option<String>,
     restartable: bool,
+    rrule: Option<String>,
+    #[serde(default)]
+    skip_next: bool,
+    #[serde(default)]
+    snooze_buttons: bool,
+    #[serde(default)]
+    thread_id: Option<String>,
+    #[serde(default)]
+    thread_title: Option<String>,
+    #[serde(default = "timezone_default")]
+    timezone: String,
     tts: bool,
     #[serde(default)]
     uid: String,
@@ -143,14 +509,42 @@ pub struct Reminder {
     utc_time: NaiveDateTime,
 }
 
+#[derive(Serialize)]
+pub struct ReminderDelivery {
+    #[serde(with = "string")]
+    channel: u64,
+    thread_id: Option<String>,
+    message_id: Option<String>,
+    method: Option<String>,
+    sent_at: NaiveDateTime,
+    error: Option<String>,
+    content: String,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ReminderCsv {
+    #[serde(default)]
+    ack_button: bool,
+    #[serde(default)]
+    allowed_mentions: Option<String>,
     #[serde(with = "base64s")]
     attachment: Option<Vec<u8>>,
     attachment_name: Option<String>,
+    #[serde(default)]
+    attachments: Option<String>,
     avatar: Option<String>,
     channel: String,
     content: String,
+    #[serde(default)]
+    content_pool: Option<String>,
+    #[serde(default)]
+    content_pool_cursor: u32,
+    #[serde(default = "content_pool_mode_default")]
+    content_pool_mode: String,
+    #[serde(default)]
+    cron: Option<String>,
+    #[serde(default)]
+    delete_after: Option<u32>,
     embed_author: String,
     embed_author_url: Option<String>,
     embed_color: u32,
@@ -161,13 +555,49 @@ pub struct ReminderCsv {
     embed_thumbnail_url: Option<String>,
     embed_title: String,
     embed_fields: Option<String>,
+    #[serde(default)]
+    embeds: Option<String>,
     enabled: bool,
+    #[serde(default)]
+    escalate_after: Option<u32>,
+    #[serde(default = "escalate_limit_default")]
+    escalate_limit: u32,
+    #[serde(default)]
+    escalate_mentions: Option<String>,
+    #[serde(default)]
+    escalate_user: Option<String>,
+    #[serde(default)]
+    exclude_dates: Option<String>,
     expires: Option<NaiveDateTime>,
     interval_seconds: Option<u32>,
     interval_months: Option<u32>,
+    #[serde(default)]
+    interval_wallclock: bool,
+    #[serde(default)]
+    interval_weekdays: bool,
+    #[serde(default)]
+    max_occurrences: Option<u32>,
+    #[serde(default)]
+    missed_policy: Option<String>,
+    #[serde(default)]
+    month_anchor: Option<String>,
+    #[serde(default)]
+    month_anchor_day: Option<u32>,
     #[serde(default = "name_default")]
     name: String,
+    #[serde(default)]
+    replace_mode: Option<String>,
     restartable: bool,
+    #[serde(default)]
+    rrule: Option<String>,
+    #[serde(default)]
+    snooze_buttons: bool,
+    #[serde(default)]
+    thread_id: Option<String>,
+    #[serde(default)]
+    thread_title: Option<String>,
+    #[serde(default = "timezone_default")]
+    timezone: String,
     tts: bool,
     username: Option<String>,
     utc_time: NaiveDateTime,
@@ -177,10 +607,16 @@ pub struct ReminderCsv {
 pub struct PatchReminder {
     uid: String,
     #[serde(default)]
+    ack_button: Unset<bool>,
+    #[serde(default)]
+    allowed_mentions: Unset<Option<Json<AllowedMentions>>>,
+    #[serde(default)]
     attachment: Unset<Option<String>>,
     #[serde(default)]
     attachment_name: Unset<Option<String>>,
     #[serde(default)]
+    attachments: Unset<Vec<ReminderAttachment>>,
+    #[serde(default)]
     avatar: Unset<Option<String>>,
     #[serde(default = "channel_default")]
     #[serde(with = "string")]
@@ -188,6 +624,16 @@ pub struct PatchReminder {
     #[serde(default)]
     content: Unset<String>,
     #[serde(default)]
+    content_pool: Unset<Option<Json<Vec<String>>>>,
+    #[serde(default)]
+    content_pool_cursor: Unset<u32>,
+    #[serde(default)]
+    content_pool_mode: Unset<String>,
+    #[serde(default)]
+    cron: Unset<Option<String>>,
+    #[serde(default)]
+    delete_after: Unset<Option<u32>>,
+    #[serde(default)]
     embed_author: Unset<String>,
     #[serde(default)]
     embed_author_url: Unset<Option<String>>,
@@ -208,18 +654,56 @@ pub struct PatchReminder {
     #[serde(default)]
     embed_fields: Unset<Json<Vec<EmbedField>>>,
     #[serde(default)]
+    embeds: Unset<Vec<ReminderEmbed>>,
+    #[serde(default)]
     enabled: Unset<bool>,
     #[serde(default)]
+    escalate_after: Unset<Option<u32>>,
+    #[serde(default)]
+    escalate_limit: Unset<u32>,
+    #[serde(default)]
+    escalate_mentions: Unset<Option<String>>,
+    #[serde(default)]
+    escalate_user: Unset<Option<String>>,
+    #[serde(default)]
+    exclude_dates: Unset<Option<Json<Vec<NaiveDate>>>>,
+    #[serde(default)]
     expires: Unset<Option<NaiveDateTime>>,
     #[serde(default)]
     interval_seconds: Unset<Option<u32>>,
     #[serde(default)]
     interval_months: Unset<Option<u32>>,
     #[serde(default)]
+    interval_wallclock: Unset<bool>,
+    #[serde(default)]
+    interval_weekdays: Unset<bool>,
+    #[serde(default)]
+    max_occurrences: Unset<Option<u32>>,
+    #[serde(default)]
+    missed_policy: Unset<Option<String>>,
+    #[serde(default)]
+    month_anchor: Unset<Option<String>>,
+    #[serde(default)]
+    month_anchor_day: Unset<Option<u32>>,
+    #[serde(default)]
     name: Unset<String>,
     #[serde(default)]
+    replace_mode: Unset<Option<String>>,
+    #[serde(default)]
     restartable: Unset<bool>,
     #[serde(default)]
+    rrule: Unset<Option<String>>,
+    #[serde(default)]
+    skip_next: Unset<bool>,
+    #[serde(default)]
+    snooze_buttons: Unset<bool>,
+    #[serde(default)]
+    thread_id: Unset<Option<String>>,
+    #[serde(default)]
+    thread_title: Unset<Option<String>>,
+    #[serde(default)]
+    timezone: Unset<String>,
+    #[serde(default)]
     tts: Unset<bool>,
     #[serde(default)]
     username: Unset<Option<String>>,
@@ -302,6 +786,7 @@ pub struct TodoCsv {
 pub async fn create_reminder(
     ctx: &Context,
     pool: &Pool<MySql>,
+    wakeup: &UnboundedSender<NaiveDateTime>,
     guild_id: GuildId,
     user_id: UserId,
     reminder: Reminder,
@@ -322,6 +807,31 @@ pub async fn create_reminder(
         return Err(json!({"error": "Channel not found"}));
     }
 
+    // reminders in a thread are stored against the thread's parent channel
+    if let Some(thread_id) = &reminder.thread_id {
+        let thread = match thread_id.parse::<u64>() {
+            Ok(thread_id) => ChannelId(thread_id).to_channel(&ctx).await.ok(),
+            Err(_) => None,
+        };
+
+        if thread.and_then(|t| t.guild()).map_or(true, |t| {
+            t.parent_id != Some(ChannelId(reminder.channel)) || !is_thread(t.kind)
+        }) {
+            return Err(json!({"error": "Thread not found in the selected channel"}));
+        }
+
+        if reminder.thread_title.is_some() {
+            return Err(json!({"error": "Reminders in a thread can't open new threads"}));
+        }
+    } else if reminder.thread_title.is_none()
+        && ChannelId(reminder.channel)
+            .to_channel_cached(&ctx)
+            .and_then(|c| c.guild())
+            .map_or(false, |c| c.kind == ChannelType::Forum)
+    {
+        return Err(json!({"error": "Reminders in a forum channel need a thread title"}));
+    }
+
     let channel = create_database_channel(&ctx, ChannelId(reminder.channel), pool).await;
 
     if let Err(e) = channel {
@@ -341,6 +851,7 @@ pub async fn create_reminder(
     check_length!(MAX_EMBED_AUTHOR_LENGTH, reminder.embed_author);
     check_length!(MAX_EMBED_FOOTER_LENGTH, reminder.embed_footer);
     check_length_opt!(MAX_EMBED_FIELDS, reminder.embed_fields);
+    check_length_opt!(MAX_THREAD_TITLE_LENGTH, reminder.thread_title);
     if let Some(fields) = &reminder.embed_fields {
         for field in &fields.0 {
             check_length!(MAX_EMBED_FIELD_VALUE_LENGTH, field.value);
@@ -357,6 +868,42 @@ pub async fn create_reminder(
         reminder.avatar
     );
 
+    // validate templates
+    check_template!(
+        reminder.content,
+        reminder.embed_title,
+        reminder.embed_description,
+        reminder.embed_footer
+    );
+    check_template_opt!(reminder.thread_title);
+    if let Some(fields) = &reminder.embed_fields {
+        for field in &fields.0 {
+            check_template!(field.title, field.value);
+        }
+    }
+
+    if let Some(variants) = &reminder.content_pool {
+        if variants.0.len() > MAX_CONTENT_POOL {
+            return Err(json!({
+                "error": format!("Content pool can hold at most {} variants", MAX_CONTENT_POOL)
+            }));
+        }
+
+        for variant in &variants.0 {
+            check_length!(MAX_CONTENT_LENGTH, variant);
+            check_template!(variant);
+        }
+    }
+
+    if !CONTENT_POOL_MODES.contains(&reminder.content_pool_mode.as_str()) {
+        return Err(json!({"error": "Content pool mode not recognised"}));
+    }
+
+    check_extras(
+        reminder.attachments.as_ref().map_or(&[][..], |a| a.0.as_slice()),
+        reminder.embeds.as_ref().map_or(&[][..], |e| e.0.as_slice()),
+    )?;
+
     // validate urls
     check_url_opt!(
         reminder.embed_footer_url,
@@ -377,10 +924,152 @@ pub async fn create_reminder(
         {
             return Err(json!({"error": "Interval too short"}));
         }
+    } else if reminder.interval_weekdays {
+        return Err(json!({"error": "Weekdays only needs an interval"}));
+    } else if reminder.interval_wallclock {
+        return Err(json!({"error": "Keep local time needs an interval"}));
+    }
+
+    if let Some(anchor) = &reminder.month_anchor {
+        if !MONTH_ANCHORS.contains(&anchor.as_str()) {
+            return Err(json!({"error": "Invalid monthly anchor"}));
+        }
+
+        if reminder.interval_months.unwrap_or(0) == 0 {
+            return Err(json!({"error": "Monthly anchor needs an interval in months"}));
+        }
+    }
+
+    if reminder.month_anchor_day.map_or(false, |day| day < 1 || day > 31) {
+        return Err(json!({"error": "Invalid monthly anchor day"}));
+    }
+
+    let timezone = match reminder.timezone.parse::<Tz>() {
+        Ok(timezone) => timezone,
+        Err(_) => return Err(json!({"error": "Timezone not recognised"})),
+    };
+
+    // clamped reminders keep the day they were created on, in the reminder's timezone
+    let month_anchor_day = reminder.month_anchor_day.or_else(|| {
+        reminder.month_anchor.as_ref().map(|_| local_day(reminder.utc_time, &reminder.timezone))
+    });
+
+    if let Some(max_occurrences) = reminder.max_occurrences {
+        if max_occurrences == 0 {
+            return Err(json!({"error": "Occurrence limit must be at least 1"}));
+        }
+
+        if reminder.interval_seconds.is_none()
+            && reminder.interval_months.is_none()
+            && reminder.cron.is_none()
+            && reminder.rrule.is_none()
+        {
+            return Err(json!({"error": "Occurrence limit needs a repeating reminder"}));
+        }
+    }
+
+    if let Some(Json(mentions)) = &reminder.allowed_mentions {
+        mentions.validate()?;
+    }
+
+    // reminders can't ping more than their creator could
+    let allowed_mentions = if can_mention_everyone(
+        ctx,
+        guild_id,
+        ChannelId(reminder.channel),
+        user_id,
+    )
+    .await
+    {
+        reminder.allowed_mentions
+    } else {
+        match reminder.allowed_mentions {
+            Some(Json(mentions)) if mentions.allows_everyone() => {
+                return Err(
+                    json!({"error": "You need the Mention Everyone permission to allow @everyone"}),
+                );
+            }
+            Some(mentions) => Some(mentions),
+            None => Some(Json(AllowedMentions::users_and_roles())),
+        }
+    };
+
+    if reminder.delete_after == Some(0) {
+        return Err(json!({"error": "Delete after must be at least 1 second"}));
+    }
+
+    if let Some(escalate_after) = reminder.escalate_after {
+        if escalate_after == 0 {
+            return Err(json!({"error": "Escalate after must be at least 1 second"}));
+        }
+
+        if !reminder.ack_button {
+            return Err(json!({"error": "Escalation needs the \"Mark done\" button"}));
+        }
+
+        if reminder.escalate_mentions.is_none() && reminder.escalate_user.is_none() {
+            return Err(json!({"error": "Escalation needs mentions or a backup user"}));
+        }
+    }
+
+    if reminder.escalate_limit < 1 || reminder.escalate_limit > MAX_ESCALATIONS {
+        return Err(json!({
+            "error": format!("Escalation limit must be between 1 and {}", MAX_ESCALATIONS)
+        }));
+    }
+
+    check_length_opt!(MAX_ESCALATE_MENTIONS_LENGTH, reminder.escalate_mentions);
+    if reminder.escalate_mentions.as_ref().map_or(false, |m| !is_mention_list(m)) {
+        return Err(json!({"error": "Escalation mentions must be role or user mentions"}));
+    }
+
+    if reminder.escalate_user.as_ref().map_or(false, |u| u.parse::<u64>().is_err()) {
+        return Err(json!({"error": "Invalid backup user"}));
+    }
+
+    if let Some(mode) = &reminder.replace_mode {
+        if !REPLACE_MODES.contains(&mode.as_str()) {
+            return Err(json!({"error": "Replace mode not recognised"}));
+        }
+
+        if reminder.interval_seconds.is_none()
+            && reminder.interval_months.is_none()
+            && reminder.cron.is_none()
+            && reminder.rrule.is_none()
+        {
+            return Err(json!({"error": "Replace mode needs a repeating reminder"}));
+        }
+    }
+
+    if reminder.missed_policy.as_ref().map_or(false, |p| !MISSED_POLICIES.contains(&p.as_str())) {
+        return Err(json!({"error": "Invalid missed reminder policy"}));
+    }
+
+    if let Some(cron) = &reminder.cron {
+        if reminder.interval_seconds.is_some() || reminder.interval_months.is_some() {
+            return Err(json!({"error": "Interval and cron can't be used together"}));
+        }
+
+        check_cron!(cron, timezone);
+    }
+
+    if let Some(rrule) = &reminder.rrule {
+        if reminder.interval_seconds.is_some()
+            || reminder.interval_months.is_some()
+            || reminder.cron.is_some()
+        {
+            return Err(json!({"error": "RRULE can't be used with an interval or cron"}));
+        }
+
+        check_rrule!(rrule);
     }
 
     // check patreon if necessary
-    if reminder.interval_seconds.is_some() || reminder.interval_months.is_some() {
+    if reminder.interval_seconds.is_some()
+        || reminder.interval_months.is_some()
+        || reminder.cron.is_some()
+        || reminder.rrule.is_some()
+    {
         if !check_guild_subscription(&ctx, guild_id).await
             && !check_subscription(&ctx, user_id).await
         {
@@ -398,11 +1087,18 @@ pub async fn create_reminder(
     match sqlx::query!(
         "INSERT INTO reminders (
          uid,
+         ack_button,
+         allowed_mentions,
          attachment,
          attachment_name,
          channel_id,
          avatar,
          content,
+         content_pool,
+         content_pool_cursor,
+         content_pool_mode,
+         cron,
+         delete_after,
          embed_author,
          embed_author_url,
          embed_color,
@@ -414,21 +1110,45 @@ pub async fn create_reminder(
          embed_title,
          embed_fields,
          enabled,
+         escalate_after,
+         escalate_limit,
+         escalate_mentions,
+         escalate_user,
+         exclude_dates,
          expires,
          interval_seconds,
          interval_months,
+         interval_wallclock,
+         interval_weekdays,
+         max_occurrences,
+         missed_policy,
+         month_anchor,
+         month_anchor_day,
          name,
+         replace_mode,
          restartable,
+         rrule,
+         snooze_buttons,
+         thread_id,
+         thread_title,
+         timezone,
          tts,
          username,
          `utc_time`
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         new_uid,
+        reminder.ack_button,
+        allowed_mentions,
         attachment_data,
         reminder.attachment_name,
         channel,
         reminder.avatar,
         reminder.content,
+        reminder.content_pool,
+        reminder.content_pool_cursor,
+        reminder.content_pool_mode,
+        reminder.cron,
+        reminder.delete_after,
         reminder.embed_author,
         reminder.embed_author_url,
         reminder.embed_color,
@@ -440,11 +1160,28 @@ pub async fn create_reminder(
         reminder.embed_title,
         reminder.embed_fields,
         reminder.enabled,
+        reminder.escalate_after,
+        reminder.escalate_limit,
+        reminder.escalate_mentions,
+        reminder.escalate_user,
+        reminder.exclude_dates,
         reminder.expires,
         reminder.interval_seconds,
         reminder.interval_months,
+        reminder.interval_wallclock,
+        reminder.interval_weekdays,
+        reminder.max_occurrences,
+        reminder.missed_policy,
+        reminder.month_anchor,
+        month_anchor_day,
         name,
+        reminder.replace_mode,
         reminder.restartable,
+        reminder.rrule,
+        reminder.snooze_buttons,
+        reminder.thread_id,
+        reminder.thread_title,
+        reminder.timezone,
         reminder.tts,
         reminder.username,
         reminder.utc_time,
@@ -452,47 +1189,116 @@ pub async fn create_reminder(
     .execute(pool)
     .await
     {
-        Ok(_) => sqlx::query_as_unchecked!(
-            Reminder,
-            "SELECT
-             reminders.attachment,
-             reminders.attachment_name,
-             reminders.avatar,
-             channels.channel,
-             reminders.content,
-             reminders.embed_author,
-             reminders.embed_author_url,
-             reminders.embed_color,
-             reminders.embed_description,
-             reminders.embed_footer,
-             reminders.embed_footer_url,
-             reminders.embed_image_url,
-             reminders.embed_thumbnail_url,
-             reminders.embed_title,
-             reminders.embed_fields,
-             reminders.enabled,
-             reminders.expires,
-             reminders.interval_seconds,
-             reminders.interval_months,
-             reminders.name,
-             reminders.restartable,
-             reminders.tts,
-             reminders.uid,
-             reminders.username,
-             reminders.utc_time
-            FROM reminders
-            LEFT JOIN channels ON channels.id = reminders.channel_id
-            WHERE uid = ?",
-            new_uid
-        )
-        .fetch_one(pool)
-        .await
-        .map(|r| Ok(json!(r)))
-        .unwrap_or_else(|e| {
-            warn!("Failed to complete SQL query: {:?}", e);
+        Ok(_) => {
+            if let Err(e) = set_reminder_extras(
+                pool,
+                &new_uid,
+                reminder.attachments.as_ref().map(|a| a.0.as_slice()),
+                reminder.embeds.as_ref().map(|e| e.0.as_slice()),
+            )
+            .await
+            {
+                warn!("Error in `create_reminder`: Could not save attachments and embeds: {:?}", e);
 
-            Err(json!({"error": "Could not load reminder"}))
-        }),
+                return Err(json!({"error": "Could not save attachments and embeds"}));
+            }
+
+            sqlx::query_as_unchecked!(
+                Reminder,
+                "SELECT
+                 reminders.ack_button,
+                 reminders.allowed_mentions,
+                 reminders.attachment,
+                 reminders.attachment_name,
+                 (
+                     SELECT JSON_ARRAYAGG(JSON_OBJECT(
+                         'id', reminder_attachments.id,
+                         'name', reminder_attachments.name,
+                         'size', LENGTH(reminder_attachments.data)
+                     ))
+                     FROM reminder_attachments WHERE reminder_attachments.reminder_id = reminders.id
+                 ) AS attachments,
+                 reminders.avatar,
+                 channels.channel,
+                 reminders.content,
+                 reminders.content_pool,
+                 reminders.content_pool_cursor,
+                 reminders.content_pool_mode,
+                 reminders.cron,
+                 reminders.delete_after,
+                 reminders.embed_author,
+                 reminders.embed_author_url,
+                 reminders.embed_color,
+                 reminders.embed_description,
+                 reminders.embed_footer,
+                 reminders.embed_footer_url,
+                 reminders.embed_image_url,
+                 reminders.embed_thumbnail_url,
+                 reminders.embed_title,
+                 reminders.embed_fields,
+                 (
+                     SELECT JSON_ARRAYAGG(JSON_OBJECT(
+                         'author', reminder_embeds.author,
+                         'author_url', reminder_embeds.author_url,
+                         'color', reminder_embeds.color,
+                         'description', reminder_embeds.description,
+                         'fields', IFNULL(reminder_embeds.fields, JSON_ARRAY()),
+                         'footer', reminder_embeds.footer,
+                         'footer_url', reminder_embeds.footer_url,
+                         'image_url', reminder_embeds.image_url,
+                         'thumbnail_url', reminder_embeds.thumbnail_url,
+                         'title', reminder_embeds.title
+                     ))
+                     FROM reminder_embeds WHERE reminder_embeds.reminder_id = reminders.id
+                 ) AS embeds,
+                 reminders.enabled,
+                 reminders.escalate_after,
+                 reminders.escalate_limit,
+                 reminders.escalate_mentions,
+                 CAST(reminders.escalate_user AS CHAR) AS escalate_user,
+                 reminders.exclude_dates,
+                 reminders.expires,
+                 reminders.failed,
+                 reminders.interval_seconds,
+                 reminders.interval_months,
+                 reminders.interval_wallclock,
+                 reminders.interval_weekdays,
+                 reminders.max_occurrences,
+                 reminders.missed_policy,
+                 reminders.month_anchor,
+                 reminders.month_anchor_day,
+                 reminders.name,
+                 reminders.occurrences,
+                 reminders.replace_mode,
+                 reminders.restartable,
+                 reminders.rrule,
+                 reminders.skip_next,
+                 reminders.snooze_buttons,
+                 CAST(reminders.thread_id AS CHAR) AS thread_id,
+                 reminders.thread_title,
+                 reminders.timezone,
+                 reminders.tts,
+                 reminders.uid,
+                 reminders.username,
+                 reminders.utc_time
+                FROM reminders
+                LEFT JOIN channels ON channels.id = reminders.channel_id
+                WHERE uid = ?",
+                new_uid
+            )
+            .fetch_one(pool)
+            .await
+            .map(|r| {
+                let _ = wakeup.send(r.utc_time);
+
+                Ok(json!(r))
+            })
+            .unwrap_or_else(|e| {
+                warn!("Failed to complete SQL query: {:?}", e);
+
+                Err(json!({"error": "Could not load reminder"}))
+            })
+        }
 
         Err(e) => {
             warn!("Error in `create_reminder`: Could not execute query: {:?}", e);
@@ -502,6 +1308,60 @@ pub async fn create_reminder(
     }
 }
 
+fn is_thread(kind: ChannelType) -> bool {
+    matches!(kind, ChannelType::PublicThread | ChannelType::PrivateThread | ChannelType::NewsThread)
+}
+
+/// Whether a user may mention @everyone in a channel. Threads take their parent's overwrites.
+/// Anything missing from the cache is fetched over HTTP.
+async fn can_mention_everyone(
+    ctx: &Context,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    user_id: UserId,
+) -> bool {
+    let mut channel = match channel_id.to_channel(ctx).await.ok().and_then(|c| c.guild()) {
+        Some(channel) if channel.guild_id == guild_id => channel,
+        _ => return false,
+    };
+
+    if is_thread(channel.kind) {
+        channel = match channel.parent_id {
+            Some(parent) => match parent.to_channel(ctx).await.ok().and_then(|c| c.guild()) {
+                Some(parent) => parent,
+                None => return false,
+            },
+            None => return false,
+        };
+    }
+
+    let member = match guild_id.member(ctx, user_id).await {
+        Ok(member) => member,
+        Err(_) => return false,
+    };
+
+    let permissions = match guild_id.to_guild_cached(ctx) {
+        Some(guild) => guild.user_permissions_in(&channel, &member),
+        None => match ctx.http.get_guild(guild_id.0).await {
+            Ok(guild) => guild.user_permissions_in(&channel, &member),
+            Err(e) => Err(e),
+        },
+    };
+
+    permissions.map_or(false, |permissions| permissions.mention_everyone())
+}
+
+/// Whether a string is a space-separated list of role and user mentions.
+fn is_mention_list(mentions: &str) -> bool {
+    mentions.split_whitespace().all(|mention| {
+        mention
+            .strip_prefix("<@")
+            .and_then(|m| m.strip_suffix('>'))
+            .map(|m| m.trim_start_matches(|c| c == '&' || c == '!'))
+            .map_or(false, |id| id.parse::<u64>().is_ok())
+    })
+}
+
 async fn create_database_channel(
     ctx: impl AsRef<Http>,
     channel: ChannelId,
@@ -203,7 +203,8 @@ async function serialize_reminder(node, mode) {
         interval = get_interval(node);
 
         utc_time = luxon.DateTime.fromISO(
-            node.querySelector('input[name="time"]').value
+            node.querySelector('input[name="time"]').value,
+            { zone: timezone }
         ).setZone("UTC");
         if (utc_time.invalid) {
             return { error: "Time provided invalid." };
@@ -282,9 +283,16 @@ async function serialize_reminder(node, mode) {
     );
     const embed_title = node.querySelector('textarea[name="embed_title"]').value;
 
+    const content_pool = node
+        .querySelector('textarea[name="content_pool_text"]')
+        .value.split(/^---$/m)
+        .map((variant) => variant.trim())
+        .filter((variant) => variant.length > 0);
+
     if (
         attachment === null &&
         content.length == 0 &&
+        content_pool.length == 0 &&
         embed_author_url === null &&
         embed_author.length == 0 &&
         embed_description.length == 0 &&
@@ -302,11 +310,18 @@ async function serialize_reminder(node, mode) {
         // if we're editing a reminder, ignore this field
         enabled: enabled,
         restartable: false,
+        ack_button: node.querySelector('input[name="ack_button"]').checked,
+        allowed_mentions: serialize_allowed_mentions(node),
         attachment: attachment,
         attachment_name: attachment_name,
         avatar: has_source(node.querySelector("img.discord-avatar").src),
         channel: node.querySelector("select.channel-selector").value,
         content: content,
+        content_pool: content_pool.length > 0 ? content_pool : null,
+        content_pool_mode: node.querySelector('select[name="content_pool_mode"]').value,
+        delete_after:
+            parseInt(node.querySelector('input[name="delete_after_minutes"]').value) * 60 ||
+            null,
         embed_author_url: embed_author_url,
         embed_author: embed_author,
         embed_color: color,
@@ -317,16 +332,83 @@ async function serialize_reminder(node, mode) {
         embed_thumbnail_url: embed_thumbnail_url,
         embed_title: embed_title,
         embed_fields: fields,
+        escalate_after:
+            parseInt(node.querySelector('input[name="escalate_after_minutes"]').value) * 60 ||
+            null,
+        escalate_limit:
+            parseInt(node.querySelector('input[name="escalate_limit"]').value) || 1,
+        escalate_mentions:
+            node.querySelector('input[name="escalate_mentions"]').value || null,
+        escalate_user: node.querySelector('input[name="escalate_user"]').value || null,
         expires: expiration_time,
         interval_seconds: mode !== "template" ? interval.seconds : null,
         interval_months: mode !== "template" ? interval.months : null,
+        interval_wallclock:
+            mode !== "template" &&
+            node.querySelector('input[name="interval_wallclock"]').checked,
+        interval_weekdays:
+            mode !== "template" &&
+            node.querySelector('input[name="interval_weekdays"]').checked,
+        max_occurrences:
+            mode !== "template"
+                ? parseInt(node.querySelector('input[name="max_occurrences"]').value) || null
+                : null,
+        exclude_dates:
+            mode !== "template"
+                ? node
+                      .querySelector('input[name="exclude_dates"]')
+                      .value.split(",")
+                      .map((date) => date.trim())
+                      .filter((date) => date.length > 0)
+                : null,
+        month_anchor:
+            mode !== "template" && interval.months
+                ? node.querySelector('select[name="month_anchor"]').value || null
+                : null,
         name: node.querySelector('input[name="name"]').value,
+        snooze_buttons: node.querySelector('input[name="snooze_buttons"]').checked,
+        thread_id: node.querySelector('input[name="thread_id"]').value || null,
+        thread_title: node.querySelector('input[name="thread_title"]').value || null,
+        timezone: timezone,
+        replace_mode:
+            mode !== "template"
+                ? node.querySelector('select[name="replace_mode"]').value || null
+                : null,
         tts: node.querySelector('input[name="tts"]').checked,
         username: node.querySelector('input[name="username"]').value,
         utc_time: utc_time,
     };
 }
 
+function serialize_allowed_mentions(node) {
+    const mode = node.querySelector('select[name="mentions_mode"]').value;
+    const allowlist = node.querySelector('input[name="mention_allowlist"]').value;
+
+    let allowed = {
+        parse: mode === "" || mode === "none" ? [] : mode.split(" "),
+        users: [],
+        roles: [],
+    };
+
+    for (const mention of allowlist.matchAll(/<@(!|&)?(\d+)>/g)) {
+        if (mention[1] === "&") {
+            allowed.roles.push(mention[2]);
+        } else {
+            allowed.users.push(mention[2]);
+        }
+    }
+
+    if (mode === "" && allowed.users.length === 0 && allowed.roles.length === 0) {
+        return null;
+    }
+
+    // Discord rejects a type that is both parsed and listed
+    if (allowed.users.length > 0) allowed.parse = allowed.parse.filter((p) => p !== "users");
+    if (allowed.roles.length > 0) allowed.parse = allowed.parse.filter((p) => p !== "roles");
+
+    return allowed;
+}
+
 function deserialize_reminder(reminder, frame, mode) {
     // populate channels
     set_channels(frame.querySelector("select.channel-selector"));
@@ -366,8 +448,50 @@ function deserialize_reminder(reminder, frame, mode) {
             .insertBefore(embed_field, lastChild);
     }
 
+    frame.querySelector('input[name="ack_button"]').checked = reminder["ack_button"];
+    frame.querySelector('input[name="snooze_buttons"]').checked = reminder["snooze_buttons"];
+
+    if (reminder["content_pool"]) {
+        frame.querySelector('textarea[name="content_pool_text"]').value =
+            reminder["content_pool"].join("\n---\n");
+    }
+
+    if (reminder["allowed_mentions"]) {
+        const allowed = reminder["allowed_mentions"];
+        const listed = allowed["users"]
+            .map((id) => `<@${id}>`)
+            .concat(allowed["roles"].map((id) => `<@&${id}>`));
+
+        // listed types were dropped from parse when serializing, so add them back
+        const parsed = ["users", "roles", "everyone"].filter(
+            (p) =>
+                allowed["parse"].includes(p) ||
+                (p === "users" && allowed["users"].length > 0) ||
+                (p === "roles" && allowed["roles"].length > 0)
+        );
+
+        let $mode = frame.querySelector('select[name="mentions_mode"]');
+        $mode.value = parsed.join(" ");
+        if ($mode.value !== parsed.join(" ") || parsed.length === 0) $mode.value = "none";
+        frame.querySelector('input[name="mention_allowlist"]').value = listed.join(" ");
+    }
+
+    if (reminder["escalate_after"]) {
+        frame.querySelector('input[name="escalate_after_minutes"]').value =
+            reminder["escalate_after"] / 60;
+    }
+
+    if (reminder["delete_after"]) {
+        frame.querySelector('input[name="delete_after_minutes"]').value =
+            reminder["delete_after"] / 60;
+    }
+
     if (mode !== "template") {
         if (reminder["interval_seconds"]) update_interval(frame);
+        frame.querySelector('input[name="interval_wallclock"]').checked =
+            reminder["interval_wallclock"];
+        frame.querySelector('input[name="interval_weekdays"]').checked =
+            reminder["interval_weekdays"];
 
         let $enableBtn = frame.querySelector(".disable-enable");
         $enableBtn.dataset["action"] = reminder["enabled"] ? "disable" : "enable";
@@ -467,6 +591,18 @@ document.addEventListener("remindersLoaded", (event) => {
                 });
         });
 
+        node.querySelector("button.skip-reminder").addEventListener("click", () => {
+            fetch(`/dashboard/api/guild/${guild}/reminders/${reminder["uid"]}/skip`, {
+                method: "POST",
+            })
+                .then((response) => response.json())
+                .then((data) => {
+                    if (data.error) {
+                        show_error(data.error);
+                    }
+                });
+        });
+
         node.querySelector("button.delete-reminder").addEventListener("click", () => {
             $deleteReminderBtn.dataset["uid"] = reminder["uid"];
             $deleteReminderBtn.closest(".modal").classList.toggle("is-active");